@@ -0,0 +1,75 @@
+//! Streams a large amount of child output through the PTY master and reports throughput for
+//! `IoFd::read` against the previous strategy of duplicating the descriptor on every call.
+//!
+//! Run with `cargo bench --bench throughput`.
+
+use nix::fcntl::{fcntl, FcntlArg};
+use std::{
+    fs::File,
+    io::{self, Read},
+    os::fd::{AsFd, AsRawFd},
+    process::Command,
+    time::{Duration, Instant},
+};
+use termu::{IoFd, Terminal};
+
+const BYTES: usize = 64 * 1024 * 1024;
+const ROUNDS: usize = 3;
+
+fn spawn() -> (Terminal, IoFd) {
+    let mut command = Command::new("sh");
+    command.args([
+        "-c",
+        &format!("stty raw -echo; exec head -c {BYTES} /dev/zero"),
+    ]);
+
+    let mut terminal = Terminal::builder(command).size(24, 80).build().unwrap();
+    let stdout = terminal.stdout.take().unwrap();
+    (terminal, stdout)
+}
+
+/// What `IoFd::read` used to do: `fcntl` to check the fd, then `dup` + `read` + `close`
+fn read_dup(fd: &IoFd, buf: &mut [u8]) -> io::Result<usize> {
+    fcntl(fd.as_raw_fd(), FcntlArg::F_GETFD)?;
+    File::from(fd.as_fd().try_clone_to_owned()?).read(buf)
+}
+
+fn drain(mut read: impl FnMut(&mut [u8]) -> io::Result<usize>) -> (usize, Duration) {
+    let mut buf = [0u8; 4096];
+    let mut total = 0;
+    let start = Instant::now();
+
+    loop {
+        match read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => total += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            // Linux reports the slave hanging up as EIO
+            Err(_) => break,
+        }
+    }
+
+    (total, start.elapsed())
+}
+
+fn report(name: &str, (total, elapsed): (usize, Duration)) -> f64 {
+    let mbps = total as f64 / (1024.0 * 1024.0) / elapsed.as_secs_f64();
+    println!("{name:>12}: {total} bytes in {elapsed:.2?} ({mbps:.1} MiB/s)");
+    mbps
+}
+
+fn main() {
+    let (mut direct, mut dup) = (0f64, 0f64);
+
+    for _ in 0..ROUNDS {
+        let (mut terminal, mut stdout) = spawn();
+        direct += report("direct", drain(|buf| stdout.read(buf)));
+        terminal.wait().unwrap();
+
+        let (mut terminal, stdout) = spawn();
+        dup += report("dup-per-call", drain(|buf| read_dup(&stdout, buf)));
+        terminal.wait().unwrap();
+    }
+
+    println!("speedup: {:.2}x", direct / dup);
+}
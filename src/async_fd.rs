@@ -0,0 +1,105 @@
+use std::{
+    io::{self, Read, Write},
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+use tokio::io::{unix::AsyncFd, AsyncRead, AsyncWrite, ReadBuf};
+
+use crate::{ExitReason, IoFd, Terminal};
+
+/// An [`IoFd`] registered with the tokio reactor.
+///
+/// `O_NONBLOCK` lives on the open file description, which every handle onto the master shares,
+/// so once one half is async the other should be converted as well.
+pub struct AsyncIoFd(AsyncFd<IoFd>);
+
+impl IoFd {
+    pub fn into_async(self) -> io::Result<AsyncIoFd> {
+        self.set_nonblocking(true)?;
+        Ok(AsyncIoFd(AsyncFd::new(self)?))
+    }
+}
+
+impl AsyncIoFd {
+    pub fn get_ref(&self) -> &IoFd {
+        self.0.get_ref()
+    }
+
+    pub fn into_inner(self) -> io::Result<IoFd> {
+        let fd = self.0.into_inner();
+        fd.set_nonblocking(false)?;
+        Ok(fd)
+    }
+}
+
+impl AsyncRead for AsyncIoFd {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        loop {
+            let mut guard = ready!(self.0.poll_read_ready(cx))?;
+            let unfilled = buf.initialize_unfilled();
+
+            match guard.try_io(|fd| fd.get_ref().read(unfilled)) {
+                Ok(result) => {
+                    buf.advance(result?);
+                    return Poll::Ready(Ok(()));
+                }
+                Err(_would_block) => continue,
+            }
+        }
+    }
+}
+
+impl AsyncWrite for AsyncIoFd {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        loop {
+            let mut guard = ready!(self.0.poll_write_ready(cx))?;
+
+            match guard.try_io(|fd| fd.get_ref().write(buf)) {
+                Ok(result) => return Poll::Ready(result),
+                Err(_would_block) => continue,
+            }
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    // A PTY can't be half-closed; dropping every handle onto the master is what hangs it up
+    fn poll_shutdown(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl Terminal {
+    /// Resolves once the child exits, reaping it
+    pub async fn exited(&mut self) -> io::Result<ExitReason> {
+        if let Some(reason) = self.try_wait()? {
+            return Ok(reason);
+        }
+
+        #[cfg(target_os = "linux")]
+        if let Some(pidfd) = self.pidfd()? {
+            let pidfd = AsyncFd::with_interest(pidfd, tokio::io::Interest::READABLE)?;
+            let _guard = pidfd.readable().await?;
+        }
+
+        // A process we didn't spawn stays a zombie after its pidfd fires until its own parent
+        // reaps it, so keep checking rather than blocking in `wait`
+        loop {
+            if let Some(reason) = self.try_wait()? {
+                return Ok(reason);
+            }
+
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+    }
+}
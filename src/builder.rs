@@ -0,0 +1,107 @@
+use nix::{pty::Winsize, sys::termios::Termios};
+use std::{ffi::OsStr, path::Path, process::Command};
+
+use crate::{DropPolicy, Error, Terminal};
+
+pub const DEFAULT_TERM: &str = "xterm-256color";
+pub const DEFAULT_COLORTERM: &str = "truecolor";
+
+pub struct TerminalBuilder {
+    command: Command,
+    size: Option<Winsize>,
+    termios: Option<Termios>,
+    drop_policy: DropPolicy,
+}
+
+impl TerminalBuilder {
+    pub fn new(command: Command) -> Self {
+        Self {
+            command,
+            size: None,
+            termios: None,
+            drop_policy: DropPolicy::default(),
+        }
+    }
+
+    pub fn size(self, rows: u16, cols: u16) -> Self {
+        self.winsize(Winsize {
+            ws_row: rows,
+            ws_col: cols,
+            ws_xpixel: 0,
+            ws_ypixel: 0,
+        })
+    }
+
+    pub fn winsize(mut self, size: Winsize) -> Self {
+        self.size = Some(size);
+        self
+    }
+
+    pub fn termios(mut self, termios: Termios) -> Self {
+        self.termios = Some(termios);
+        self
+    }
+
+    pub fn drop_policy(mut self, policy: DropPolicy) -> Self {
+        self.drop_policy = policy;
+        self
+    }
+
+    pub fn cwd(mut self, dir: impl AsRef<Path>) -> Self {
+        self.command.current_dir(dir);
+        self
+    }
+
+    pub fn env(mut self, key: impl AsRef<OsStr>, value: impl AsRef<OsStr>) -> Self {
+        self.command.env(key, value);
+        self
+    }
+
+    pub fn envs<I, K, V>(mut self, vars: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<OsStr>,
+        V: AsRef<OsStr>,
+    {
+        self.command.envs(vars);
+        self
+    }
+
+    pub fn env_remove(mut self, key: impl AsRef<OsStr>) -> Self {
+        self.command.env_remove(key);
+        self
+    }
+
+    pub fn env_clear(mut self) -> Self {
+        self.command.env_clear();
+        self
+    }
+
+    pub fn term(self, term: impl AsRef<OsStr>) -> Self {
+        self.env("TERM", term)
+    }
+
+    pub fn colorterm(self, colorterm: impl AsRef<OsStr>) -> Self {
+        self.env("COLORTERM", colorterm)
+    }
+
+    pub fn build(mut self) -> Result<Terminal, Error> {
+        // Only fill in the defaults when the caller hasn't set (or removed) them
+        for (key, value) in [("TERM", DEFAULT_TERM), ("COLORTERM", DEFAULT_COLORTERM)] {
+            if !self.command.get_envs().any(|(k, _)| k == key) {
+                self.command.env(key, value);
+            }
+        }
+
+        let mut terminal =
+            Terminal::open_with(&mut self.command, self.size.as_ref(), self.termios.as_ref())?;
+        terminal.set_drop_policy(self.drop_policy);
+        Ok(terminal)
+    }
+}
+
+impl From<Command> for TerminalBuilder {
+    fn from(command: Command) -> Self {
+        Self::new(command)
+    }
+}
@@ -0,0 +1,91 @@
+use nix::errno::Errno;
+use std::{error, fmt, io};
+
+/// Where opening a [`Terminal`](crate::Terminal) went wrong, with the errno that stage failed on
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// `posix_openpt`
+    OpenMaster(Errno),
+    /// The descriptor handed to [`Terminal::from_master`](crate::Terminal::from_master) isn't a
+    /// PTY master
+    NotMaster(Errno),
+    /// `grantpt` / `unlockpt`
+    Grant(Errno),
+    /// `ptsname_r` or opening the slave device
+    OpenSlave(Errno),
+    /// Setting up the pair before the child starts: `FD_CLOEXEC`, window size, termios, `dup`
+    Configure(Errno),
+    /// Closing the master and calling `setsid` in the child
+    Session(Errno),
+    /// Making the slave the child's controlling terminal with `TIOCSCTTY`
+    ControllingTty(Errno),
+    /// Spawning or exec-ing the command itself
+    Exec(Errno),
+    /// Attaching a command while the previous process is still running
+    Busy,
+}
+
+impl Error {
+    pub fn errno(&self) -> Errno {
+        match *self {
+            Self::OpenMaster(e)
+            | Self::NotMaster(e)
+            | Self::Grant(e)
+            | Self::OpenSlave(e)
+            | Self::Configure(e)
+            | Self::Session(e)
+            | Self::ControllingTty(e)
+            | Self::Exec(e) => e,
+            Self::Busy => Errno::EBUSY,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let stage = match self {
+            Self::OpenMaster(_) => "open PTY master",
+            Self::NotMaster(_) => "adopt PTY master",
+            Self::Grant(_) => "grant/unlock PTY slave",
+            Self::OpenSlave(_) => "open PTY slave",
+            Self::Configure(_) => "configure PTY",
+            Self::Session(_) => "start new session in child",
+            Self::ControllingTty(_) => "set controlling terminal in child",
+            Self::Exec(_) => "spawn child",
+            Self::Busy => "attach while the previous process is running",
+        };
+
+        write!(f, "failed to {stage}: {}", self.errno())
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::OpenMaster(e)
+            | Self::NotMaster(e)
+            | Self::Grant(e)
+            | Self::OpenSlave(e)
+            | Self::Configure(e)
+            | Self::Session(e)
+            | Self::ControllingTty(e)
+            | Self::Exec(e) => Some(e),
+            Self::Busy => None,
+        }
+    }
+}
+
+impl From<Error> for io::Error {
+    fn from(value: Error) -> Self {
+        io::Error::new(io::Error::from(value.errno()).kind(), value)
+    }
+}
+
+/// The errno behind an `io::Error`, for the few that don't come from the OS
+pub(crate) fn errno(e: io::Error) -> Errno {
+    match e.raw_os_error() {
+        Some(raw) => Errno::from_raw(raw),
+        None if e.kind() == io::ErrorKind::InvalidInput => Errno::EINVAL,
+        None => Errno::UnknownErrno,
+    }
+}
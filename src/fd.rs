@@ -0,0 +1,121 @@
+use nix::{
+    fcntl::{fcntl, FcntlArg, OFlag},
+    unistd::{read, write},
+};
+use std::{
+    io::{self, Stdin},
+    os::fd::{AsFd, AsRawFd, BorrowedFd, OwnedFd, RawFd},
+};
+
+/// One handle onto the PTY master. Reads and writes go straight to `read(2)` / `write(2)`
+/// on the owned descriptor, so each call costs exactly one syscall.
+pub struct IoFd(pub(crate) OwnedFd);
+
+impl IoFd {
+    pub fn exists(&self) -> bool {
+        fcntl(self.0.as_raw_fd(), FcntlArg::F_GETFD).unwrap_or(-1) != -1
+    }
+
+    /// Toggle `O_NONBLOCK`. This is shared with every other handle onto the same master; reads and
+    /// writes that can't make progress then fail with [`io::ErrorKind::WouldBlock`].
+    pub fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
+        let mut flags = OFlag::from_bits_retain(fcntl(self.0.as_raw_fd(), FcntlArg::F_GETFL)?);
+        flags.set(OFlag::O_NONBLOCK, nonblocking);
+        fcntl(self.0.as_raw_fd(), FcntlArg::F_SETFL(flags))?;
+        Ok(())
+    }
+}
+
+impl io::Read for IoFd {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        (&*self).read(buf)
+    }
+}
+
+impl io::Write for IoFd {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        (&*self).write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        (&*self).flush()
+    }
+}
+
+// Like `&File`, a shared handle is enough to do I/O on the descriptor
+impl io::Read for &IoFd {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match read(self.0.as_raw_fd(), buf) {
+            // Linux reports a hung up slave as EIO on the master rather than EOF
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            Err(nix::errno::Errno::EIO) => Ok(0),
+            r => Ok(r?),
+        }
+    }
+}
+
+impl io::Write for &IoFd {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        Ok(write(&self.0, buf)?)
+    }
+
+    // Nothing is buffered in userspace
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl AsFd for IoFd {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.0.as_fd()
+    }
+}
+
+impl AsRawFd for IoFd {
+    fn as_raw_fd(&self) -> RawFd {
+        self.0.as_raw_fd()
+    }
+}
+
+#[cfg(feature = "mio")]
+impl mio::event::Source for IoFd {
+    fn register(
+        &mut self,
+        registry: &mio::Registry,
+        token: mio::Token,
+        interests: mio::Interest,
+    ) -> io::Result<()> {
+        mio::unix::SourceFd(&self.as_raw_fd()).register(registry, token, interests)
+    }
+
+    fn reregister(
+        &mut self,
+        registry: &mio::Registry,
+        token: mio::Token,
+        interests: mio::Interest,
+    ) -> io::Result<()> {
+        mio::unix::SourceFd(&self.as_raw_fd()).reregister(registry, token, interests)
+    }
+
+    fn deregister(&mut self, registry: &mio::Registry) -> io::Result<()> {
+        mio::unix::SourceFd(&self.as_raw_fd()).deregister(registry)
+    }
+}
+
+impl From<OwnedFd> for IoFd {
+    fn from(value: OwnedFd) -> Self {
+        Self(value)
+    }
+}
+
+impl From<IoFd> for OwnedFd {
+    fn from(value: IoFd) -> Self {
+        value.0
+    }
+}
+
+impl From<Stdin> for IoFd {
+    fn from(value: Stdin) -> Self {
+        Self(value.as_fd().try_clone_to_owned().unwrap())
+    }
+}
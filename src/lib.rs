@@ -1,119 +1,187 @@
 use nix::{
-    fcntl::{
-        fcntl,
-        FcntlArg::{self, F_SETFD},
-        FdFlag, OFlag,
-    },
-    libc::{close, ioctl, setsid, TIOCSCTTY, TIOCSWINSZ},
-    pty::{grantpt, posix_openpt, ptsname_r, unlockpt, PtyMaster, Winsize},
+    fcntl::{fcntl, FcntlArg, FdFlag},
+    libc::{ioctl, TIOCSWINSZ},
+    poll::{poll, PollFd, PollFlags, PollTimeout},
+    sys::termios::Termios,
 };
 use std::{
-    fs::{File, OpenOptions},
-    io::{self, ErrorKind, Stdin},
-    os::{
-        fd::{AsFd, AsRawFd, OwnedFd},
-        unix::process::CommandExt,
-    },
-    process::{Child, Command, ExitStatus},
+    io,
+    os::fd::{AsFd, AsRawFd, FromRawFd, IntoRawFd, OwnedFd, RawFd},
+    path::{Path, PathBuf},
+    process::{Child, Command},
+    sync::Arc,
 };
 
+#[cfg(feature = "tokio")]
+mod async_fd;
+mod builder;
+mod error;
+mod fd;
+pub mod parser;
+mod process;
+mod pty;
+mod readiness;
+pub mod screen;
+mod split;
+pub mod termios;
+#[cfg(feature = "tokio")]
+pub use async_fd::AsyncIoFd;
+pub use builder::{TerminalBuilder, DEFAULT_COLORTERM, DEFAULT_TERM};
+pub use error::Error;
+pub use fd::IoFd;
+pub use nix::{
+    pty::{PtyMaster, Winsize},
+    sys::signal::Signal,
+    unistd::Pid,
+};
+pub use process::{DropPolicy, ExitReason, Process};
+pub use pty::PtyPair;
+pub use readiness::Readiness;
+pub use split::{PtyControl, PtyReader, PtyWriter};
+
+use error::errno;
+use process::{Foreign, LivePid};
+
 pub struct Terminal {
-    owner: PtyMaster,
+    owner: OwnedFd,
     pub stdin: Option<IoFd>,
     pub stdout: Option<IoFd>,
     pub child: Option<Child>,
+    foreign: Option<Foreign>,
+    live: Arc<LivePid>,
+    slave_path: PathBuf,
+    drop_policy: DropPolicy,
 }
 
 impl Terminal {
-    pub fn open(command: &mut Command) -> io::Result<Self> {
-        let owner = posix_openpt(OFlag::O_RDWR | OFlag::O_NOCTTY)?;
-        grantpt(&owner)?;
-        unlockpt(&owner)?;
+    /// Spawn `command` on a fresh PTY.
+    ///
+    /// `command` comes back changed: its stdio is reset to
+    /// [`Stdio::null`](std::process::Stdio::null) and it carries a `pre_exec` hook that stays
+    /// inert from then on. Every spawn adds another such hook, so a `Command` reused for a great
+    /// many spawns keeps growing.
+    pub fn open(command: &mut Command) -> Result<Self, Error> {
+        Self::open_with(command, None, None)
+    }
 
-        let mut flags = FdFlag::from_bits_retain(fcntl(owner.as_raw_fd(), FcntlArg::F_GETFD)?);
-        flags |= FdFlag::FD_CLOEXEC;
+    pub fn builder(command: Command) -> TerminalBuilder {
+        TerminalBuilder::new(command)
+    }
 
-        fcntl(owner.as_raw_fd(), F_SETFD(flags))?;
+    pub(crate) fn open_with(
+        command: &mut Command,
+        size: Option<&Winsize>,
+        termios: Option<&Termios>,
+    ) -> Result<Self, Error> {
+        let pair = PtyPair::open()?;
+
+        if let Some(size) = size {
+            pair.resize(*size).map_err(|e| Error::Configure(errno(e)))?;
+        }
 
-        let pup = OpenOptions::new()
-            .read(true)
-            .write(true)
-            .open(ptsname_r(&owner)?)?;
+        if let Some(termios) = termios {
+            pair.set_termios(termios)
+                .map_err(|e| Error::Configure(errno(e)))?;
+        }
+
+        Self::from_pair(pair, command)
+    }
+
+    /// Spawn `command` on an already opened pair. The slave is closed once the child has it.
+    /// `command` is left changed the same way as by [`Terminal::open`].
+    pub fn from_pair(pair: PtyPair, command: &mut Command) -> Result<Self, Error> {
+        let (owner, slave, slave_path) = pair.into_parts();
+        let owner = unsafe { OwnedFd::from_raw_fd(owner.into_raw_fd()) };
+
+        let mut terminal = Self::adopt(owner, slave_path)?;
+        terminal.child = Some(pty::spawn(
+            terminal.owner.as_raw_fd(),
+            slave.as_fd(),
+            command,
+        )?);
+        terminal.live.set(terminal.pid());
+
+        Ok(terminal)
+    }
 
-        command.stdin(pup.try_clone()?);
-        command.stdout(pup.try_clone()?);
-        command.stderr(pup.try_clone()?);
+    /// Wrap a PTY master opened elsewhere, e.g. one received from another process over a Unix
+    /// socket, along with whatever is running on it. What's adopted is left running on drop
+    /// ([`DropPolicy::Detach`]) unless another policy is set.
+    pub fn from_master(master: OwnedFd, process: Option<Process>) -> Result<Self, Error> {
+        let slave_path = pty::slave_path(master.as_fd())?;
 
-        unsafe {
-            let o_fd = owner.as_raw_fd();
-            command.pre_exec(move || {
-                if close(o_fd) != 0 || setsid() < 0 || ioctl(0, TIOCSCTTY.into(), 1) != 0 {
-                    return Err(io::Error::last_os_error());
-                }
+        let mut flags = FdFlag::from_bits_retain(
+            fcntl(master.as_raw_fd(), FcntlArg::F_GETFD).map_err(Error::Configure)?,
+        );
+        flags |= FdFlag::FD_CLOEXEC;
+        fcntl(master.as_raw_fd(), FcntlArg::F_SETFD(flags)).map_err(Error::Configure)?;
 
-                Ok(())
-            });
+        let mut terminal = Self::adopt(master, slave_path)?;
+        terminal.drop_policy = DropPolicy::Detach;
+        match process {
+            Some(Process::Child(child)) => terminal.child = Some(child),
+            Some(Process::Pid(pid)) => terminal.foreign = Some(Foreign::new(pid)),
+            None => {}
         }
+        terminal.live.set(terminal.pid());
+
+        Ok(terminal)
+    }
+
+    fn adopt(owner: OwnedFd, slave_path: PathBuf) -> Result<Self, Error> {
+        let dup = |fd: &OwnedFd| fd.try_clone().map_err(|e| Error::Configure(errno(e)));
 
         Ok(Self {
-            stdin: Some(IoFd(owner.as_fd().try_clone_to_owned()?)),
-            stdout: Some(IoFd(owner.as_fd().try_clone_to_owned()?)),
-            child: Some(command.spawn()?),
+            stdin: Some(IoFd(dup(&owner)?)),
+            stdout: Some(IoFd(dup(&owner)?)),
+            child: None,
+            foreign: None,
+            live: Arc::default(),
             owner,
+            slave_path,
+            drop_policy: DropPolicy::default(),
         })
     }
 
-    pub fn resize(&self, size: Winsize) -> io::Result<()> {
-        match unsafe { ioctl(self.owner.as_raw_fd(), TIOCSWINSZ, &size) != 0 } {
-            true => Err(io::Error::last_os_error()),
-            false => Ok(()),
+    /// Spawn another command on this PTY once the previous child has exited, e.g. to run
+    /// several programs one after another against the same screen. `command` is left changed the
+    /// same way as by [`Terminal::open`].
+    pub fn attach(&mut self, command: &mut Command) -> Result<(), Error> {
+        if matches!(self.try_wait(), Ok(None)) {
+            return Err(Error::Busy);
         }
-    }
-}
 
-pub struct IoFd(OwnedFd);
-impl IoFd {
-    pub fn exists(&self) -> bool {
-        fcntl(self.0.as_raw_fd(), FcntlArg::F_GETFD).unwrap_or(-1) != -1
+        let slave = pty::open_slave(&self.slave_path)?;
+        self.child = Some(pty::spawn(self.owner.as_raw_fd(), slave.as_fd(), command)?);
+        self.foreign = None;
+        self.live.set(self.pid());
+
+        Ok(())
     }
-}
 
-impl io::Read for IoFd {
-    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
-        match self.exists() {
-            true => File::from(self.0.try_clone()?).read(buf),
-            _ => Err(io::Error::new(
-                ErrorKind::BrokenPipe,
-                "File Descriptor Closed",
-            )),
-        }
+    pub fn slave_path(&self) -> &Path {
+        &self.slave_path
     }
-}
 
-impl io::Write for IoFd {
-    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
-        match self.exists() {
-            true => File::from(self.0.try_clone()?).write(buf),
-            _ => Err(io::Error::new(
-                ErrorKind::BrokenPipe,
-                "File Descriptor Closed",
-            )),
-        }
+    pub fn resize(&self, size: Winsize) -> io::Result<()> {
+        set_winsize(self.owner.as_raw_fd(), &size)
     }
 
-    fn flush(&mut self) -> io::Result<()> {
-        match self.exists() {
-            true => File::from(self.0.try_clone()?).flush(),
-            _ => Err(io::Error::new(
-                ErrorKind::BrokenPipe,
-                "File Descriptor Closed",
-            )),
-        }
+    /// Whether every holder of the slave side (the child and anything it forked) has closed it.
+    /// Output written before the hangup can still be read from the master.
+    pub fn is_hung_up(&self) -> io::Result<bool> {
+        let mut fds = [PollFd::new(self.owner.as_fd(), PollFlags::POLLIN)];
+        poll(&mut fds, PollTimeout::ZERO)?;
+
+        Ok(fds[0]
+            .revents()
+            .is_some_and(|events| events.contains(PollFlags::POLLHUP)))
     }
 }
 
-impl From<Stdin> for IoFd {
-    fn from(value: Stdin) -> Self {
-        Self(value.as_fd().try_clone_to_owned().unwrap())
+fn set_winsize(fd: RawFd, size: &Winsize) -> io::Result<()> {
+    match unsafe { ioctl(fd, TIOCSWINSZ, size) != 0 } {
+        true => Err(io::Error::last_os_error()),
+        false => Ok(()),
     }
 }
@@ -0,0 +1,481 @@
+//! A DEC ANSI compatible escape sequence parser, after Paul Williams' state machine
+//! (<https://vt100.net/emu/dec_ansi_parser>), with UTF-8 in the ground state and `:`
+//! subparameters.
+//!
+//! All state lives in [`Parser`], so input can be fed in chunks split at any byte.
+
+use std::{fmt, mem, str};
+
+pub const MAX_PARAMS: usize = 32;
+pub const MAX_INTERMEDIATES: usize = 2;
+pub const MAX_OSC_PARAMS: usize = 16;
+pub const MAX_STRING_LEN: usize = 1 << 16;
+
+/// Receives whatever the parser recognises
+#[allow(unused_variables)]
+pub trait Perform {
+    /// A printable character
+    fn print(&mut self, c: char) {}
+
+    /// A C0 control
+    fn execute(&mut self, byte: u8) {}
+
+    /// Start of a DCS string. What follows is passed to [`Perform::put`] until
+    /// [`Perform::unhook`].
+    fn hook(&mut self, params: &Params, intermediates: &[u8], ignore: bool, action: char) {}
+
+    fn put(&mut self, byte: u8) {}
+
+    fn unhook(&mut self) {}
+
+    /// An OSC string, split on `;`
+    fn osc_dispatch(&mut self, params: &[&[u8]], bell_terminated: bool) {}
+
+    /// `ignore` is set when there were more parameters or intermediates than fit
+    fn csi_dispatch(&mut self, params: &Params, intermediates: &[u8], ignore: bool, action: char) {}
+
+    fn esc_dispatch(&mut self, intermediates: &[u8], ignore: bool, byte: u8) {}
+
+    /// A complete SOS, PM or APC string
+    fn string_dispatch(&mut self, kind: StringKind, data: &[u8]) {}
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum StringKind {
+    #[default]
+    Sos,
+    Pm,
+    Apc,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum State {
+    #[default]
+    Ground,
+    Escape,
+    EscapeIntermediate,
+    CsiEntry,
+    CsiParam,
+    CsiIntermediate,
+    CsiIgnore,
+    DcsEntry,
+    DcsParam,
+    DcsIntermediate,
+    DcsPassthrough,
+    DcsIgnore,
+    OscString,
+    SosPmApcString,
+}
+
+/// CSI / DCS parameters. Each parameter is a group of one value plus any `:` subparameters.
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct Params {
+    values: [u16; MAX_PARAMS],
+    /// At the index each group starts at, how many values it holds
+    groups: [u8; MAX_PARAMS],
+    len: usize,
+    group_start: usize,
+}
+
+impl Params {
+    /// The number of parameter groups
+    pub fn len(&self) -> usize {
+        self.iter().count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn iter(&self) -> ParamsIter<'_> {
+        ParamsIter {
+            params: self,
+            index: 0,
+        }
+    }
+
+    /// The first value of the group at `index`, if it's there and non-zero
+    pub fn get(&self, index: usize) -> Option<u16> {
+        self.iter()
+            .nth(index)
+            .and_then(|group| group.first().copied())
+            .filter(|value| *value != 0)
+    }
+
+    fn is_full(&self) -> bool {
+        self.len == MAX_PARAMS
+    }
+
+    fn clear(&mut self) {
+        self.len = 0;
+        self.group_start = 0;
+    }
+
+    /// Start a new group
+    fn push(&mut self, value: u16) {
+        self.group_start = self.len;
+        self.groups[self.len] = 1;
+        self.values[self.len] = value;
+        self.len += 1;
+    }
+
+    /// Add a subparameter to the current group
+    fn extend(&mut self, value: u16) {
+        self.groups[self.group_start] += 1;
+        self.values[self.len] = value;
+        self.len += 1;
+    }
+}
+
+impl fmt::Debug for Params {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<'a> IntoIterator for &'a Params {
+    type Item = &'a [u16];
+    type IntoIter = ParamsIter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+pub struct ParamsIter<'a> {
+    params: &'a Params,
+    index: usize,
+}
+
+impl<'a> Iterator for ParamsIter<'a> {
+    type Item = &'a [u16];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index >= self.params.len {
+            return None;
+        }
+
+        let len = self.params.groups[self.index] as usize;
+        let group = &self.params.values[self.index..self.index + len];
+        self.index += len;
+        Some(group)
+    }
+}
+
+/// A partially received UTF-8 sequence
+#[derive(Clone, Copy, Default)]
+struct Utf8 {
+    buf: [u8; 4],
+    len: u8,
+    need: u8,
+}
+
+#[derive(Default)]
+pub struct Parser {
+    state: State,
+    params: Params,
+    param: u16,
+    subparam: bool,
+    intermediates: [u8; MAX_INTERMEDIATES],
+    intermediate_len: usize,
+    ignoring: bool,
+    string: Vec<u8>,
+    string_kind: StringKind,
+    utf8: Utf8,
+}
+
+impl Parser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn advance<P: Perform>(&mut self, performer: &mut P, bytes: &[u8]) {
+        for &byte in bytes {
+            self.advance_byte(performer, byte);
+        }
+    }
+
+    fn advance_byte<P: Perform>(&mut self, performer: &mut P, byte: u8) {
+        if self.state == State::Ground {
+            return self.ground(performer, byte);
+        }
+
+        // Transitions that apply from every state but ground
+        match byte {
+            0x18 | 0x1a => {
+                self.abort(performer, false);
+                performer.execute(byte);
+                self.state = State::Ground;
+                return;
+            }
+            0x1b => {
+                // This is also how ST (`ESC \`) ends a string
+                self.abort(performer, true);
+                self.enter_escape();
+                return;
+            }
+            _ => {}
+        }
+
+        match self.state {
+            State::Ground => unreachable!(),
+            State::Escape => match byte {
+                0x00..=0x1f => performer.execute(byte),
+                0x20..=0x2f => {
+                    self.collect(byte);
+                    self.state = State::EscapeIntermediate;
+                }
+                b'P' => self.enter_params(State::DcsEntry),
+                b'[' => self.enter_params(State::CsiEntry),
+                b']' => self.enter_string(State::OscString, StringKind::default()),
+                b'X' => self.enter_string(State::SosPmApcString, StringKind::Sos),
+                b'^' => self.enter_string(State::SosPmApcString, StringKind::Pm),
+                b'_' => self.enter_string(State::SosPmApcString, StringKind::Apc),
+                0x30..=0x7e => self.esc_dispatch(performer, byte),
+                _ => {}
+            },
+            State::EscapeIntermediate => match byte {
+                0x00..=0x1f => performer.execute(byte),
+                0x20..=0x2f => self.collect(byte),
+                0x30..=0x7e => self.esc_dispatch(performer, byte),
+                _ => {}
+            },
+            State::CsiEntry | State::CsiParam => match byte {
+                0x00..=0x1f => performer.execute(byte),
+                b'0'..=b'9' | b';' | b':' => {
+                    self.param(byte);
+                    self.state = State::CsiParam;
+                }
+                // Private markers are only allowed before any parameters
+                0x3c..=0x3f if self.state == State::CsiEntry => {
+                    self.collect(byte);
+                    self.state = State::CsiParam;
+                }
+                0x3c..=0x3f => self.state = State::CsiIgnore,
+                0x20..=0x2f => {
+                    self.collect(byte);
+                    self.state = State::CsiIntermediate;
+                }
+                0x40..=0x7e => self.csi_dispatch(performer, byte),
+                _ => {}
+            },
+            State::CsiIntermediate => match byte {
+                0x00..=0x1f => performer.execute(byte),
+                0x20..=0x2f => self.collect(byte),
+                0x30..=0x3f => self.state = State::CsiIgnore,
+                0x40..=0x7e => self.csi_dispatch(performer, byte),
+                _ => {}
+            },
+            State::CsiIgnore => match byte {
+                0x00..=0x1f => performer.execute(byte),
+                0x40..=0x7e => self.state = State::Ground,
+                _ => {}
+            },
+            State::DcsEntry | State::DcsParam => match byte {
+                b'0'..=b'9' | b';' | b':' => {
+                    self.param(byte);
+                    self.state = State::DcsParam;
+                }
+                0x3c..=0x3f if self.state == State::DcsEntry => {
+                    self.collect(byte);
+                    self.state = State::DcsParam;
+                }
+                0x3c..=0x3f => self.state = State::DcsIgnore,
+                0x20..=0x2f => {
+                    self.collect(byte);
+                    self.state = State::DcsIntermediate;
+                }
+                0x40..=0x7e => self.hook(performer, byte),
+                _ => {}
+            },
+            State::DcsIntermediate => match byte {
+                0x20..=0x2f => self.collect(byte),
+                0x30..=0x3f => self.state = State::DcsIgnore,
+                0x40..=0x7e => self.hook(performer, byte),
+                _ => {}
+            },
+            State::DcsPassthrough => match byte {
+                0x7f => {}
+                _ => performer.put(byte),
+            },
+            State::DcsIgnore => {}
+            State::OscString => match byte {
+                0x07 => {
+                    self.osc_dispatch(performer, true);
+                    self.state = State::Ground;
+                }
+                0x00..=0x1f => {}
+                _ => self.push_string(byte),
+            },
+            State::SosPmApcString => self.push_string(byte),
+        }
+    }
+
+    fn ground<P: Perform>(&mut self, performer: &mut P, byte: u8) {
+        if self.utf8.need > 0 {
+            if let 0x80..=0xbf = byte {
+                self.utf8.buf[self.utf8.len as usize] = byte;
+                self.utf8.len += 1;
+
+                if self.utf8.len == self.utf8.need {
+                    let bytes = &self.utf8.buf[..self.utf8.len as usize];
+                    let c = str::from_utf8(bytes).map_or(char::REPLACEMENT_CHARACTER, |s| {
+                        s.chars().next().unwrap_or(char::REPLACEMENT_CHARACTER)
+                    });
+
+                    self.utf8 = Utf8::default();
+                    performer.print(c);
+                }
+
+                return;
+            }
+
+            // Cut short; report what we had, then take this byte as usual
+            self.utf8 = Utf8::default();
+            performer.print(char::REPLACEMENT_CHARACTER);
+        }
+
+        match byte {
+            0x1b => self.enter_escape(),
+            0x00..=0x1f => performer.execute(byte),
+            0x20..=0x7e => performer.print(byte as char),
+            0x7f => {}
+            0xc2..=0xf4 => {
+                self.utf8.buf[0] = byte;
+                self.utf8.len = 1;
+                self.utf8.need = match byte {
+                    0xc2..=0xdf => 2,
+                    0xe0..=0xef => 3,
+                    _ => 4,
+                };
+            }
+            _ => performer.print(char::REPLACEMENT_CHARACTER),
+        }
+    }
+
+    /// Leave the current state for an ESC, CAN or SUB, dispatching any string in progress only if
+    /// it was terminated rather than cancelled
+    fn abort<P: Perform>(&mut self, performer: &mut P, dispatch: bool) {
+        match self.state {
+            State::DcsPassthrough => performer.unhook(),
+            State::OscString if dispatch => self.osc_dispatch(performer, false),
+            State::SosPmApcString if dispatch => {
+                performer.string_dispatch(self.string_kind, &self.string)
+            }
+            _ => {}
+        }
+    }
+
+    fn enter_escape(&mut self) {
+        self.intermediate_len = 0;
+        self.ignoring = false;
+        self.state = State::Escape;
+    }
+
+    fn enter_params(&mut self, state: State) {
+        self.params.clear();
+        self.param = 0;
+        self.subparam = false;
+        self.intermediate_len = 0;
+        self.ignoring = false;
+        self.state = state;
+    }
+
+    fn enter_string(&mut self, state: State, kind: StringKind) {
+        self.string.clear();
+        self.string_kind = kind;
+        self.state = state;
+    }
+
+    fn collect(&mut self, byte: u8) {
+        match self.intermediate_len == MAX_INTERMEDIATES {
+            true => self.ignoring = true,
+            false => {
+                self.intermediates[self.intermediate_len] = byte;
+                self.intermediate_len += 1;
+            }
+        }
+    }
+
+    fn param(&mut self, byte: u8) {
+        match byte {
+            b';' => {
+                self.commit_param();
+                self.subparam = false;
+            }
+            b':' => {
+                self.commit_param();
+                self.subparam = true;
+            }
+            _ => {
+                self.param = self
+                    .param
+                    .saturating_mul(10)
+                    .saturating_add((byte - b'0') as u16);
+            }
+        }
+    }
+
+    /// Finish the value being accumulated, either as a new group or after a `:` in the current one
+    fn commit_param(&mut self) {
+        match self.params.is_full() {
+            true => self.ignoring = true,
+            false if self.subparam => self.params.extend(self.param),
+            false => self.params.push(self.param),
+        }
+        self.param = 0;
+    }
+
+    fn push_string(&mut self, byte: u8) {
+        if self.string.len() < MAX_STRING_LEN {
+            self.string.push(byte);
+        }
+    }
+
+    fn esc_dispatch<P: Perform>(&mut self, performer: &mut P, byte: u8) {
+        performer.esc_dispatch(
+            &self.intermediates[..self.intermediate_len],
+            self.ignoring,
+            byte,
+        );
+        self.state = State::Ground;
+    }
+
+    fn csi_dispatch<P: Perform>(&mut self, performer: &mut P, byte: u8) {
+        self.commit_param();
+        performer.csi_dispatch(
+            &self.params,
+            &self.intermediates[..self.intermediate_len],
+            self.ignoring,
+            byte as char,
+        );
+        self.state = State::Ground;
+    }
+
+    fn hook<P: Perform>(&mut self, performer: &mut P, byte: u8) {
+        self.commit_param();
+        performer.hook(
+            &self.params,
+            &self.intermediates[..self.intermediate_len],
+            self.ignoring,
+            byte as char,
+        );
+        self.state = State::DcsPassthrough;
+    }
+
+    fn osc_dispatch<P: Perform>(&mut self, performer: &mut P, bell_terminated: bool) {
+        let string = mem::take(&mut self.string);
+        let mut params = [&[][..]; MAX_OSC_PARAMS];
+        let mut len = 0;
+
+        // The last parameter keeps any further `;`s
+        for param in string.splitn(MAX_OSC_PARAMS, |b| *b == b';') {
+            params[len] = param;
+            len += 1;
+        }
+
+        performer.osc_dispatch(&params[..len], bell_terminated);
+        self.string = string;
+        self.string.clear();
+    }
+}
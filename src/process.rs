@@ -0,0 +1,345 @@
+use nix::{
+    errno::Errno,
+    sys::signal::{kill, killpg, Signal},
+    sys::wait::{waitpid, WaitPidFlag, WaitStatus},
+    unistd::{getpgid, getpgrp, tcgetpgrp, Pid},
+};
+use std::{
+    io::{self, ErrorKind},
+    os::{fd::AsFd, unix::process::ExitStatusExt},
+    process::{Child, ExitStatus},
+    sync::atomic::{AtomicI32, Ordering},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::Terminal;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ExitReason {
+    /// The child called `exit` with this code
+    Exited(i32),
+    /// The child was terminated by this (raw) signal number
+    Signaled(i32),
+    /// The process is gone, but it wasn't our child or its status couldn't be made sense of
+    Unknown,
+}
+
+impl ExitReason {
+    pub fn success(&self) -> bool {
+        *self == Self::Exited(0)
+    }
+
+    pub fn code(&self) -> Option<i32> {
+        match self {
+            Self::Exited(code) => Some(*code),
+            Self::Signaled(_) | Self::Unknown => None,
+        }
+    }
+
+    pub fn signal(&self) -> Option<Signal> {
+        match self {
+            Self::Exited(_) | Self::Unknown => None,
+            Self::Signaled(signal) => Signal::try_from(*signal).ok(),
+        }
+    }
+}
+
+impl From<ExitStatus> for ExitReason {
+    fn from(status: ExitStatus) -> Self {
+        match (status.code(), status.signal()) {
+            (Some(code), _) => Self::Exited(code),
+            (None, Some(signal)) => Self::Signaled(signal),
+            // Stopped / continued statuses never come out of `wait`
+            (None, None) => Self::Unknown,
+        }
+    }
+}
+
+/// The process attached to a [`Terminal`] that termu didn't spawn itself
+pub enum Process {
+    Child(Child),
+    Pid(Pid),
+}
+
+impl From<Child> for Process {
+    fn from(value: Child) -> Self {
+        Self::Child(value)
+    }
+}
+
+impl From<Pid> for Process {
+    fn from(value: Pid) -> Self {
+        Self::Pid(value)
+    }
+}
+
+/// A process we only know by pid
+pub(crate) struct Foreign {
+    pid: Pid,
+    exit: Option<ExitReason>,
+}
+
+impl Foreign {
+    pub(crate) fn new(pid: Pid) -> Self {
+        Self { pid, exit: None }
+    }
+
+    fn try_wait(&mut self) -> io::Result<Option<ExitReason>> {
+        if self.exit.is_some() {
+            return Ok(self.exit);
+        }
+
+        self.exit = match waitpid(self.pid, Some(WaitPidFlag::WNOHANG)) {
+            Ok(WaitStatus::Exited(_, code)) => Some(ExitReason::Exited(code)),
+            Ok(WaitStatus::Signaled(_, signal, _)) => Some(ExitReason::Signaled(signal as i32)),
+            Ok(_) => None,
+            // Someone else's child; all we can tell is whether it's still around
+            Err(Errno::ECHILD) => match kill(self.pid, None) {
+                Err(Errno::ESRCH) => Some(ExitReason::Unknown),
+                _ => None,
+            },
+            Err(e) => return Err(e.into()),
+        };
+
+        Ok(self.exit)
+    }
+}
+
+/// The pid of the process on a [`Terminal`], shared with its [`PtyControl`](crate::PtyControl)s.
+/// Cleared once the process is reaped, so they can't signal whatever reuses the pid.
+#[derive(Debug, Default)]
+pub(crate) struct LivePid(AtomicI32);
+
+impl LivePid {
+    pub(crate) fn get(&self) -> Option<Pid> {
+        match self.0.load(Ordering::Acquire) {
+            0 => None,
+            pid => Some(Pid::from_raw(pid)),
+        }
+    }
+
+    pub(crate) fn set(&self, pid: Option<Pid>) {
+        self.0.store(pid.map_or(0, Pid::as_raw), Ordering::Release);
+    }
+}
+
+/// What dropping a [`Terminal`] does to a child that is still running
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DropPolicy {
+    /// Leave the child running (it may become a zombie if it exits later). Closing the last handle
+    /// on the master still hangs up its terminal.
+    Detach,
+    /// SIGKILL the child's process group and reap it
+    Kill,
+    /// Hang up, give the child this long to exit, then SIGKILL and reap it
+    Shutdown(Duration),
+}
+
+impl Default for DropPolicy {
+    fn default() -> Self {
+        Self::Shutdown(Duration::from_millis(100))
+    }
+}
+
+impl Terminal {
+    /// The process on the terminal. This stays around after it's reaped, but signalling it then
+    /// fails rather than reaching whatever has reused the pid.
+    pub fn pid(&self) -> Option<Pid> {
+        match (&self.child, &self.foreign) {
+            (Some(child), _) => Some(Pid::from_raw(child.id() as i32)),
+            (None, Some(foreign)) => Some(foreign.pid),
+            (None, None) => None,
+        }
+    }
+
+    /// Send a signal to the child process only
+    pub fn signal(&self, signal: Signal) -> io::Result<()> {
+        Ok(kill(self.child_pid()?, signal)?)
+    }
+
+    /// Send a signal to the child's process group (which a spawned child leads). A child that
+    /// doesn't lead a group of its own is signalled alone.
+    pub fn signal_group(&self, signal: Signal) -> io::Result<()> {
+        signal_group(self.child_pid()?, signal)
+    }
+
+    /// The process group currently in the foreground of the PTY, e.g. a job run by a shell.
+    /// Fails with [`ErrorKind::NotFound`] once the child's session has ended.
+    pub fn foreground_group(&self) -> io::Result<Pid> {
+        foreground_group(&self.owner)
+    }
+
+    /// Send a signal to the foreground process group, the way the line discipline delivers ^C
+    pub fn signal_foreground(&self, signal: Signal) -> io::Result<()> {
+        Ok(killpg(self.foreground_group()?, signal)?)
+    }
+
+    pub fn wait(&mut self) -> io::Result<ExitReason> {
+        if let Some(child) = &mut self.child {
+            let reason = child.wait()?.into();
+            self.live.set(None);
+            return Ok(reason);
+        }
+
+        loop {
+            if let Some(reason) = self.try_wait()? {
+                return Ok(reason);
+            }
+
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    pub fn try_wait(&mut self) -> io::Result<Option<ExitReason>> {
+        let reason = match (&mut self.child, &mut self.foreign) {
+            (Some(child), _) => child.try_wait()?.map(ExitReason::from),
+            (None, Some(foreign)) => foreign.try_wait()?,
+            (None, None) => return Err(no_child()),
+        };
+
+        if reason.is_some() {
+            self.live.set(None);
+        }
+
+        Ok(reason)
+    }
+
+    pub fn wait_timeout(&mut self, timeout: Duration) -> io::Result<Option<ExitReason>> {
+        let deadline = Instant::now() + timeout;
+        let mut backoff = Duration::from_millis(1);
+
+        loop {
+            if let Some(reason) = self.try_wait()? {
+                return Ok(Some(reason));
+            }
+
+            let now = Instant::now();
+            if now >= deadline {
+                return Ok(None);
+            }
+
+            thread::sleep(backoff.min(deadline - now));
+            backoff = (backoff * 2).min(Duration::from_millis(50));
+        }
+    }
+
+    pub fn drop_policy(&self) -> DropPolicy {
+        self.drop_policy
+    }
+
+    pub fn set_drop_policy(&mut self, policy: DropPolicy) {
+        self.drop_policy = policy;
+    }
+
+    /// Hang up the terminal and wait up to `timeout` for the child to exit, escalating to SIGKILL
+    /// if it doesn't. The child is always reaped. Returns `None` when there is no child to stop.
+    ///
+    /// The master stays open, so whatever the child wrote before going can still be drained from
+    /// `stdout`. Since it, `stdin`, split halves and any [`IoFd`](crate::IoFd) clones all keep the
+    /// master alive, the kernel won't hang up the slave by itself; SIGHUP is sent directly instead.
+    pub fn shutdown(&mut self, timeout: Duration) -> io::Result<Option<ExitReason>> {
+        if self.pid().is_none() {
+            return Ok(None);
+        }
+
+        if let Some(reason) = self.try_wait()? {
+            return Ok(Some(reason));
+        }
+
+        self.hang_up()?;
+
+        match self.wait_timeout(timeout)? {
+            Some(reason) => Ok(Some(reason)),
+            None => self.kill().map(Some),
+        }
+    }
+
+    /// SIGKILL the child's process group and reap the child
+    pub fn kill(&mut self) -> io::Result<ExitReason> {
+        if let Some(reason) = self.try_wait()? {
+            return Ok(reason);
+        }
+
+        ignore_missing(self.signal_group(Signal::SIGKILL))?;
+        ignore_missing(self.signal(Signal::SIGKILL))?;
+        self.wait()
+    }
+
+    /// Deliver what the kernel sends when a controlling terminal goes away
+    fn hang_up(&self) -> io::Result<()> {
+        if let Ok(group) = self.foreground_group() {
+            ignore_missing(killpg(group, Signal::SIGHUP).map_err(io::Error::from))?;
+            ignore_missing(killpg(group, Signal::SIGCONT).map_err(io::Error::from))?;
+        }
+
+        ignore_missing(self.signal_group(Signal::SIGHUP))?;
+        ignore_missing(self.signal_group(Signal::SIGCONT))
+    }
+
+    /// A descriptor that becomes readable once the child exits, where the kernel supports it
+    #[cfg(target_os = "linux")]
+    pub(crate) fn pidfd(&self) -> io::Result<Option<std::os::fd::OwnedFd>> {
+        use nix::libc::{syscall, SYS_pidfd_open};
+        use std::os::fd::{FromRawFd, OwnedFd, RawFd};
+
+        match unsafe { syscall(SYS_pidfd_open, self.child_pid()?.as_raw(), 0) } {
+            -1 => match Errno::last() {
+                Errno::ENOSYS => Ok(None),
+                e => Err(e.into()),
+            },
+            fd => Ok(Some(unsafe { OwnedFd::from_raw_fd(fd as RawFd) })),
+        }
+    }
+
+    /// The pid while it still belongs to the child, i.e. until it's reaped
+    fn child_pid(&self) -> io::Result<Pid> {
+        self.live.get().ok_or_else(no_child)
+    }
+}
+
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        if !matches!(self.try_wait(), Ok(None)) {
+            return;
+        }
+
+        let _ = match self.drop_policy {
+            DropPolicy::Detach => return,
+            DropPolicy::Kill => self.kill().map(Some),
+            DropPolicy::Shutdown(timeout) => self.shutdown(timeout),
+        };
+    }
+}
+
+/// Signal the process group `pid` leads, or only `pid` when it doesn't lead one. An adopted
+/// process may still be in our own group, which must never be signalled as a whole.
+pub(crate) fn signal_group(pid: Pid, signal: Signal) -> io::Result<()> {
+    match getpgid(Some(pid))? {
+        group if group == pid && group != getpgrp() => Ok(killpg(group, signal)?),
+        _ => Ok(kill(pid, signal)?),
+    }
+}
+
+pub(crate) fn foreground_group(master: impl AsFd) -> io::Result<Pid> {
+    // Once the session is gone Linux reports 0, which killpg would read as our own group
+    match tcgetpgrp(master)? {
+        group if group.as_raw() > 0 => Ok(group),
+        _ => Err(io::Error::new(
+            ErrorKind::NotFound,
+            "No Foreground Process Group",
+        )),
+    }
+}
+
+/// The group having already gone away is as good as the signal arriving
+fn ignore_missing(result: io::Result<()>) -> io::Result<()> {
+    match result {
+        Err(e) if e.raw_os_error() == Some(Errno::ESRCH as i32) => Ok(()),
+        r => r,
+    }
+}
+
+pub(crate) fn no_child() -> io::Error {
+    io::Error::new(ErrorKind::NotFound, "No Child Process")
+}
@@ -0,0 +1,205 @@
+use nix::{
+    errno::Errno,
+    fcntl::{
+        fcntl,
+        FcntlArg::{self, F_SETFD},
+        FdFlag, OFlag,
+    },
+    libc::{close, ioctl, setsid, write, TIOCSCTTY},
+    pty::{grantpt, posix_openpt, ptsname_r, unlockpt, PtyMaster, Winsize},
+    sys::termios::{tcsetattr, SetArg, Termios},
+    unistd::{pipe2, read},
+};
+use std::{
+    fs::OpenOptions,
+    io,
+    os::{
+        fd::{AsFd, AsRawFd, BorrowedFd, OwnedFd, RawFd},
+        unix::{fs::OpenOptionsExt, process::CommandExt},
+    },
+    path::{Path, PathBuf},
+    process::{Child, Command, Stdio},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+use crate::{error::errno, set_winsize, Error};
+
+const STAGE_SESSION: u8 = 1;
+const STAGE_CONTROLLING_TTY: u8 = 2;
+
+/// A master/slave PTY pair with nothing attached to it yet
+pub struct PtyPair {
+    master: PtyMaster,
+    slave: OwnedFd,
+    path: PathBuf,
+}
+
+impl PtyPair {
+    pub fn open() -> Result<Self, Error> {
+        let master = posix_openpt(OFlag::O_RDWR | OFlag::O_NOCTTY).map_err(Error::OpenMaster)?;
+        grantpt(&master).map_err(Error::Grant)?;
+        unlockpt(&master).map_err(Error::Grant)?;
+
+        let mut flags = FdFlag::from_bits_retain(
+            fcntl(master.as_raw_fd(), FcntlArg::F_GETFD).map_err(Error::Configure)?,
+        );
+        flags |= FdFlag::FD_CLOEXEC;
+
+        fcntl(master.as_raw_fd(), F_SETFD(flags)).map_err(Error::Configure)?;
+
+        let path = PathBuf::from(ptsname_r(&master).map_err(Error::OpenSlave)?);
+        let slave = open_slave(&path)?;
+
+        Ok(Self {
+            master,
+            slave,
+            path,
+        })
+    }
+
+    pub fn master(&self) -> &PtyMaster {
+        &self.master
+    }
+
+    pub fn slave(&self) -> BorrowedFd<'_> {
+        self.slave.as_fd()
+    }
+
+    /// The slave device, e.g. `/dev/pts/3`
+    pub fn slave_path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn resize(&self, size: Winsize) -> io::Result<()> {
+        set_winsize(self.master.as_raw_fd(), &size)
+    }
+
+    pub fn set_termios(&self, termios: &Termios) -> io::Result<()> {
+        Ok(tcsetattr(&self.slave, SetArg::TCSANOW, termios)?)
+    }
+
+    /// Start `command` in a new session with the slave as its stdio and controlling terminal.
+    /// `command` is left changed the same way as by [`Terminal::open`](crate::Terminal::open).
+    pub fn spawn(&self, command: &mut Command) -> Result<Child, Error> {
+        spawn(self.master.as_raw_fd(), self.slave.as_fd(), command)
+    }
+
+    pub fn into_parts(self) -> (PtyMaster, OwnedFd, PathBuf) {
+        (self.master, self.slave, self.path)
+    }
+}
+
+/// The slave device behind `master`, which also confirms it really is a PTY master
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub(crate) fn slave_path(master: BorrowedFd) -> Result<PathBuf, Error> {
+    use nix::libc::{c_uint, TIOCGPTN};
+
+    let mut number: c_uint = 0;
+    match unsafe { ioctl(master.as_raw_fd(), TIOCGPTN, &mut number) } {
+        -1 => Err(Error::NotMaster(Errno::last())),
+        _ => Ok(PathBuf::from(format!("/dev/pts/{number}"))),
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+pub(crate) fn slave_path(master: BorrowedFd) -> Result<PathBuf, Error> {
+    use std::{ffi::CStr, os::unix::ffi::OsStrExt, sync::Mutex};
+
+    // `ptsname` hands back a static buffer
+    static PTSNAME: Mutex<()> = Mutex::new(());
+    let _guard = PTSNAME.lock().unwrap_or_else(|e| e.into_inner());
+
+    match unsafe { nix::libc::ptsname(master.as_raw_fd()) } {
+        name if name.is_null() => Err(Error::NotMaster(Errno::last())),
+        name => Ok(PathBuf::from(std::ffi::OsStr::from_bytes(
+            unsafe { CStr::from_ptr(name) }.to_bytes(),
+        ))),
+    }
+}
+
+pub(crate) fn open_slave(path: &Path) -> Result<OwnedFd, Error> {
+    OpenOptions::new()
+        .read(true)
+        .write(true)
+        .custom_flags(OFlag::O_NOCTTY.bits())
+        .open(path)
+        .map(OwnedFd::from)
+        .map_err(|e| Error::OpenSlave(errno(e)))
+}
+
+pub(crate) fn spawn(
+    master: RawFd,
+    slave: BorrowedFd,
+    command: &mut Command,
+) -> Result<Child, Error> {
+    let dup = |fd: BorrowedFd| {
+        fd.try_clone_to_owned()
+            .map_err(|e| Error::Configure(errno(e)))
+    };
+    command.stdin(dup(slave)?);
+    command.stdout(dup(slave)?);
+    command.stderr(dup(slave)?);
+
+    // std only hands back the errno when `pre_exec` fails, so the child reports which step
+    // it was on through this pipe
+    let (report_rx, report_tx) = pipe2(OFlag::O_CLOEXEC).map_err(Error::Configure)?;
+
+    // There's no taking a hook back off a `Command`, so once this spawn is over it stands down
+    // and leaves any later spawn of the same command to the hook that one adds
+    let spent = Arc::new(AtomicBool::new(false));
+
+    unsafe {
+        let report_fd = report_tx.as_raw_fd();
+        let spent = spent.clone();
+        #[allow(clippy::useless_conversion)]
+        command.pre_exec(move || {
+            if spent.load(Ordering::Relaxed) {
+                return Ok(());
+            }
+
+            let stage = if close(master) != 0 || setsid() < 0 {
+                STAGE_SESSION
+            } else if ioctl(0, TIOCSCTTY.into(), 1) != 0 {
+                STAGE_CONTROLLING_TTY
+            } else {
+                return Ok(());
+            };
+
+            let error = io::Error::last_os_error();
+            let mut report = [stage; 5];
+            report[1..].copy_from_slice(&error.raw_os_error().unwrap_or(0).to_ne_bytes());
+            write(report_fd, report.as_ptr().cast(), report.len());
+
+            Err(error)
+        });
+    }
+
+    let child = command.spawn();
+    spent.store(true, Ordering::Relaxed);
+
+    // The command keeps its stdio around for the next spawn. If we let it hold on to the
+    // slave, the master never sees the hangup once the child exits.
+    command.stdin(Stdio::null());
+    command.stdout(Stdio::null());
+    command.stderr(Stdio::null());
+    drop(report_tx);
+
+    child.map_err(|e| {
+        let mut report = [0u8; 5];
+
+        match read(report_rx.as_raw_fd(), &mut report) {
+            Ok(5) => {
+                let code = Errno::from_raw(i32::from_ne_bytes(report[1..].try_into().unwrap()));
+
+                match report[0] {
+                    STAGE_SESSION => Error::Session(code),
+                    _ => Error::ControllingTty(code),
+                }
+            }
+            _ => Error::Exec(errno(e)),
+        }
+    })
+}
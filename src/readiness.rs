@@ -0,0 +1,150 @@
+use bitflags::bitflags;
+use nix::{
+    errno::Errno,
+    poll::{poll, PollFd, PollFlags, PollTimeout},
+};
+use std::{
+    io::{self, ErrorKind, Read},
+    os::fd::AsFd,
+    time::{Duration, Instant},
+};
+
+use crate::{IoFd, Terminal};
+
+bitflags! {
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+    pub struct Readiness: u8 {
+        /// The master has output to read, or the slave hung up and a read will return EOF
+        const READABLE = 1 << 0;
+        /// The master can take input without blocking
+        const WRITABLE = 1 << 1;
+        /// The child has exited (and been reaped)
+        const EXITED = 1 << 2;
+    }
+}
+
+/// Without a pidfd, an exit is only noticed by checking this often
+const EXIT_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+impl IoFd {
+    /// Like `read`, but fails with [`ErrorKind::TimedOut`] if nothing arrives within `timeout`
+    pub fn read_timeout(&mut self, buf: &mut [u8], timeout: Duration) -> io::Result<usize> {
+        let mut fds = [PollFd::new(self.as_fd(), PollFlags::POLLIN)];
+
+        match poll_until(&mut fds, Some(Instant::now() + timeout))? {
+            0 => Err(io::Error::new(ErrorKind::TimedOut, "Read Timed Out")),
+            _ => self.read(buf),
+        }
+    }
+}
+
+impl Terminal {
+    /// Wait until one of `interest` is ready on the master or the child, or `timeout` passes
+    /// (`None` waits forever). Returns what's ready, which is empty on timeout, and straight
+    /// away when there's nothing to wait on.
+    pub fn poll(
+        &mut self,
+        interest: Readiness,
+        timeout: Option<Duration>,
+    ) -> io::Result<Readiness> {
+        let deadline = timeout.map(|t| Instant::now() + t);
+        let watch_exit = interest.contains(Readiness::EXITED) && self.pid().is_some();
+
+        if watch_exit && self.try_wait()?.is_some() {
+            let rest = self.poll(interest - Readiness::EXITED, Some(Duration::ZERO))?;
+            return Ok(rest | Readiness::EXITED);
+        }
+
+        let mut flags = PollFlags::empty();
+        flags.set(PollFlags::POLLIN, interest.contains(Readiness::READABLE));
+        flags.set(PollFlags::POLLOUT, interest.contains(Readiness::WRITABLE));
+
+        // Polling no descriptors at all would never wake up
+        if flags.is_empty() && !watch_exit {
+            return Ok(Readiness::empty());
+        }
+
+        #[cfg(target_os = "linux")]
+        let mut pidfd = match watch_exit {
+            true => self.pidfd()?,
+            false => None,
+        };
+        #[cfg(not(target_os = "linux"))]
+        let mut pidfd: Option<std::os::fd::OwnedFd> = None;
+
+        loop {
+            let mut fds = Vec::with_capacity(2);
+            if !flags.is_empty() {
+                fds.push(PollFd::new(self.owner.as_fd(), flags));
+            }
+            if let Some(pidfd) = &pidfd {
+                fds.push(PollFd::new(pidfd.as_fd(), PollFlags::POLLIN));
+            }
+
+            // Without a pidfd the only way to notice an exit is to keep checking
+            let slice = match watch_exit && pidfd.is_none() {
+                true => {
+                    let next = Instant::now() + EXIT_POLL_INTERVAL;
+                    Some(deadline.map_or(next, |d| d.min(next)))
+                }
+                false => deadline,
+            };
+
+            poll_until(&mut fds, slice)?;
+
+            let mut ready = Readiness::empty();
+            if !flags.is_empty() {
+                let events = fds[0].revents().unwrap_or(PollFlags::empty());
+                // After a hangup neither side blocks; reads see EOF and writes fail
+                let hup = events.intersects(PollFlags::POLLHUP | PollFlags::POLLERR);
+
+                ready.set(
+                    Readiness::READABLE,
+                    hup || events.contains(PollFlags::POLLIN),
+                );
+                ready.set(
+                    Readiness::WRITABLE,
+                    hup || events.contains(PollFlags::POLLOUT),
+                );
+                ready &= interest;
+            }
+            let exit_fired = pidfd.is_some()
+                && fds
+                    .last()
+                    .and_then(|fd| fd.revents())
+                    .is_some_and(|events| !events.is_empty());
+            drop(fds);
+
+            if watch_exit && self.try_wait()?.is_some() {
+                ready |= Readiness::EXITED;
+            } else if exit_fired {
+                // A process we didn't spawn stays a zombie until its own parent reaps it, and
+                // the pidfd stays readable the whole time
+                pidfd = None;
+            }
+
+            if !ready.is_empty() || deadline.is_some_and(|d| Instant::now() >= d) {
+                return Ok(ready);
+            }
+        }
+    }
+}
+
+/// `poll(2)` until something is ready or the deadline passes, riding out EINTR
+pub(crate) fn poll_until(fds: &mut [PollFd], deadline: Option<Instant>) -> io::Result<usize> {
+    loop {
+        let timeout = match deadline {
+            None => PollTimeout::NONE,
+            Some(deadline) => {
+                let remaining = deadline.saturating_duration_since(Instant::now());
+                PollTimeout::try_from(remaining.as_nanos().div_ceil(1_000_000))
+                    .unwrap_or(PollTimeout::MAX)
+            }
+        };
+
+        match poll(fds, timeout) {
+            Err(Errno::EINTR) => continue,
+            r => return Ok(r? as usize),
+        }
+    }
+}
@@ -0,0 +1,110 @@
+//! Switching between the primary and alternate screens
+
+use std::mem;
+
+use super::{charset::Charsets, grid::Grid, modes::ModeFlags, Cursor, Mode, Screen};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Buffer {
+    /// The normal screen, which scrolls into the scrollback
+    #[default]
+    Primary,
+    /// The screen full-screen applications draw on, with no scrollback
+    Alternate,
+}
+
+/// What DECSC saves
+#[derive(Clone, Copy, Debug, Default)]
+pub(super) struct SavedCursor {
+    pub cursor: Cursor,
+    pub origin: bool,
+    pub charsets: Charsets,
+}
+
+impl Screen {
+    pub fn buffer(&self) -> Buffer {
+        self.buffer
+    }
+
+    pub fn is_alternate(&self) -> bool {
+        self.buffer == Buffer::Alternate
+    }
+
+    /// Make `buffer` the one shown, keeping the other one (and its saved cursor) aside
+    pub(super) fn switch_buffer(&mut self, buffer: Buffer) {
+        if self.buffer != buffer {
+            mem::swap(&mut self.grid, &mut self.inactive.grid);
+            mem::swap(&mut self.saved_cursor, &mut self.inactive.saved_cursor);
+            self.buffer = buffer;
+        }
+    }
+
+    /// Modes 47, 1047 and 1049
+    pub(super) fn set_alternate_mode(&mut self, mode: u16, enabled: bool) {
+        match (mode, enabled) {
+            (47 | 1047, true) => self.switch_buffer(Buffer::Alternate),
+            (1049, true) => {
+                self.save_cursor();
+                self.switch_buffer(Buffer::Alternate);
+                self.clear_grid();
+            }
+            (47, false) => self.switch_buffer(Buffer::Primary),
+            (1047, false) => {
+                if self.is_alternate() {
+                    self.clear_grid();
+                }
+                self.switch_buffer(Buffer::Primary);
+            }
+            (1049, false) => {
+                self.switch_buffer(Buffer::Primary);
+                self.restore_cursor();
+            }
+            _ => {}
+        }
+    }
+
+    /// DECSC
+    pub(super) fn save_cursor(&mut self) {
+        self.saved_cursor = Some(SavedCursor {
+            cursor: self.cursor,
+            origin: self.mode(Mode::Origin),
+            charsets: self.charsets,
+        });
+    }
+
+    /// DECRC. Without a saved cursor this homes the cursor and resets its style.
+    pub(super) fn restore_cursor(&mut self) {
+        let SavedCursor {
+            cursor,
+            origin,
+            charsets,
+        } = self.saved_cursor.unwrap_or_default();
+
+        self.modes.set(ModeFlags::ORIGIN, origin);
+        self.charsets = charsets;
+        self.cursor.style = cursor.style;
+        self.goto(cursor.row, cursor.col);
+        self.cursor.pending_wrap = cursor.pending_wrap;
+    }
+
+    fn clear_grid(&mut self) {
+        let (rows, cols) = self.size();
+        self.grid = Grid::new(rows, cols);
+    }
+}
+
+/// The screen that isn't being shown
+#[derive(Clone, Debug)]
+pub(super) struct Inactive {
+    pub grid: Grid,
+    pub saved_cursor: Option<SavedCursor>,
+}
+
+impl Inactive {
+    pub fn new(rows: usize, cols: usize) -> Self {
+        Self {
+            grid: Grid::new(rows, cols),
+            saved_cursor: None,
+        }
+    }
+}
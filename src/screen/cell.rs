@@ -0,0 +1,197 @@
+use bitflags::bitflags;
+use std::{fmt, str};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Color {
+    #[default]
+    Default,
+    /// 0-7 are the standard colours, 8-15 their bright versions, then the 256 colour palette
+    Indexed(u8),
+    Rgb(u8, u8, u8),
+}
+
+bitflags! {
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+    pub struct Attrs: u16 {
+        const BOLD = 1 << 0;
+        const DIM = 1 << 1;
+        const ITALIC = 1 << 2;
+        const BLINK = 1 << 3;
+        const INVERSE = 1 << 4;
+        const HIDDEN = 1 << 5;
+        const STRIKETHROUGH = 1 << 6;
+        const OVERLINE = 1 << 7;
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Underline {
+    #[default]
+    None,
+    Single,
+    Double,
+    Curly,
+    Dotted,
+    Dashed,
+}
+
+/// How a cell is drawn; also the cursor's current SGR state
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Style {
+    pub fg: Color,
+    pub bg: Color,
+    pub attrs: Attrs,
+    pub underline: Underline,
+    /// `Default` means the underline follows the foreground
+    pub underline_color: Color,
+}
+
+impl Style {
+    /// What erasing leaves behind: only the background survives
+    pub fn blank(&self) -> Self {
+        Self {
+            bg: self.bg,
+            ..Self::default()
+        }
+    }
+}
+
+/// The text of a cell: a single character kept inline, or a whole cluster of them
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Grapheme(Repr);
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+enum Repr {
+    Char { buf: [u8; 4], len: u8 },
+    Cluster(Box<str>),
+}
+
+impl Grapheme {
+    /// What the second column of a wide character holds
+    const EMPTY: Self = Self(Repr::Char {
+        buf: [0; 4],
+        len: 0,
+    });
+
+    pub fn as_str(&self) -> &str {
+        match &self.0 {
+            // Only ever filled from a `char`
+            Repr::Char { buf, len } => str::from_utf8(&buf[..*len as usize]).unwrap_or_default(),
+            Repr::Cluster(cluster) => cluster,
+        }
+    }
+
+    pub(crate) fn push(&mut self, c: char) {
+        let mut cluster = String::from(self.as_str());
+        cluster.push(c);
+        self.0 = Repr::Cluster(cluster.into_boxed_str());
+    }
+}
+
+impl From<char> for Grapheme {
+    fn from(c: char) -> Self {
+        let mut buf = [0; 4];
+        let len = c.encode_utf8(&mut buf).len() as u8;
+        Self(Repr::Char { buf, len })
+    }
+}
+
+impl Default for Grapheme {
+    fn default() -> Self {
+        Self::from(' ')
+    }
+}
+
+impl fmt::Debug for Grapheme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl fmt::Display for Grapheme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Cell {
+    grapheme: Grapheme,
+    pub style: Style,
+    /// 2 for a wide character, whose second column is a spacer of width 0
+    width: u8,
+}
+
+impl Cell {
+    pub fn new(c: char, style: Style) -> Self {
+        Self::with_width(c, 1, style)
+    }
+
+    pub(crate) fn with_width(c: char, width: usize, style: Style) -> Self {
+        Self {
+            grapheme: c.into(),
+            style,
+            width: width as u8,
+        }
+    }
+
+    /// An empty cell erased with `style`
+    pub fn blank(style: &Style) -> Self {
+        Self {
+            grapheme: Grapheme::default(),
+            style: style.blank(),
+            width: 1,
+        }
+    }
+
+    /// The second column of a wide character
+    pub(crate) fn spacer(style: &Style) -> Self {
+        Self {
+            grapheme: Grapheme::EMPTY,
+            style: *style,
+            width: 0,
+        }
+    }
+
+    /// Empty for the spacer following a wide character
+    pub fn grapheme(&self) -> &str {
+        self.grapheme.as_str()
+    }
+
+    pub fn width(&self) -> usize {
+        self.width as usize
+    }
+
+    pub fn is_wide(&self) -> bool {
+        self.width == 2
+    }
+
+    pub fn is_spacer(&self) -> bool {
+        self.width == 0
+    }
+
+    pub fn is_blank(&self) -> bool {
+        self.grapheme() == " "
+    }
+
+    /// Add a combining mark or other continuation of the grapheme
+    pub(crate) fn push(&mut self, c: char) {
+        self.grapheme.push(c);
+    }
+
+    pub(crate) fn set_width(&mut self, width: usize) {
+        self.width = width as u8;
+    }
+}
+
+impl Default for Cell {
+    fn default() -> Self {
+        Self::blank(&Style::default())
+    }
+}
+
+impl fmt::Display for Cell {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.grapheme())
+    }
+}
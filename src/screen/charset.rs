@@ -0,0 +1,133 @@
+//! Character set designation (G0-G3), locking and single shifts, and translating the sets into
+//! Unicode
+
+use super::Screen;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub(super) enum Charset {
+    #[default]
+    Ascii,
+    /// Line drawing
+    DecSpecialGraphics,
+    /// ASCII with £ in place of #
+    Uk,
+    /// Mostly Latin-1
+    DecSupplemental,
+}
+
+impl Charset {
+    /// The set a designation's intermediates and final byte name
+    fn designated(intermediates: &[u8], byte: u8) -> Option<Self> {
+        match (intermediates, byte) {
+            ([], b'B') => Some(Self::Ascii),
+            ([], b'0') => Some(Self::DecSpecialGraphics),
+            ([], b'A') => Some(Self::Uk),
+            ([], b'<') | ([b'%'], b'5') => Some(Self::DecSupplemental),
+            _ => None,
+        }
+    }
+
+    /// `c` is in 0x20-0x7e, as the set is invoked into GL
+    fn translate(self, c: char) -> char {
+        match self {
+            Self::Ascii => c,
+            Self::Uk if c == '#' => '£',
+            Self::Uk => c,
+            Self::DecSpecialGraphics => match c {
+                '_' => '\u{a0}',
+                '`' => '◆',
+                'a' => '▒',
+                'b' => '␉',
+                'c' => '␌',
+                'd' => '␍',
+                'e' => '␊',
+                'f' => '°',
+                'g' => '±',
+                'h' => '␤',
+                'i' => '␋',
+                'j' => '┘',
+                'k' => '┐',
+                'l' => '┌',
+                'm' => '└',
+                'n' => '┼',
+                'o' => '⎺',
+                'p' => '⎻',
+                'q' => '─',
+                'r' => '⎼',
+                's' => '⎽',
+                't' => '├',
+                'u' => '┤',
+                'v' => '┴',
+                'w' => '┬',
+                'x' => '│',
+                'y' => '≤',
+                'z' => '≥',
+                '{' => 'π',
+                '|' => '≠',
+                '}' => '£',
+                '~' => '·',
+                c => c,
+            },
+            Self::DecSupplemental => match c {
+                ' ' => c,
+                '(' => '¤',
+                'W' => 'Œ',
+                ']' => 'Ÿ',
+                'w' => 'œ',
+                '}' => 'ÿ',
+                // The rest lines up with Latin-1
+                c => char::from_u32(c as u32 + 0x80).unwrap_or(c),
+            },
+        }
+    }
+}
+
+/// The designated sets and which are invoked
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(super) struct Charsets {
+    sets: [Charset; 4],
+    /// The set invoked into GL, by SI / SO / LS2 / LS3
+    gl: usize,
+    /// SS2 / SS3 for the next character only
+    single_shift: Option<usize>,
+}
+
+impl Charsets {
+    /// Translate a printed character through whichever set is in effect for it. Only GL is
+    /// translated: input arrives as UTF-8, so 0xa0-0xff are Latin-1 text rather than GR codes.
+    pub fn translate(&mut self, c: char) -> char {
+        let single_shift = self.single_shift.take();
+
+        match c {
+            '\x20'..='\x7e' => self.sets[single_shift.unwrap_or(self.gl)].translate(c),
+            _ => c,
+        }
+    }
+}
+
+impl Screen {
+    /// SCS: `ESC ( F` for G0, `)` for G1, `*` for G2 and `+` for G3
+    pub(super) fn designate_charset(&mut self, intermediates: &[u8], byte: u8) {
+        let slot = match intermediates.first() {
+            Some(b'(') => 0,
+            Some(b')') => 1,
+            Some(b'*') => 2,
+            Some(b'+') => 3,
+            _ => return,
+        };
+
+        if let Some(charset) = Charset::designated(&intermediates[1..], byte) {
+            self.charsets.sets[slot] = charset;
+        }
+    }
+
+    /// SI (0), SO (1), LS2 and LS3
+    pub(super) fn invoke_gl(&mut self, slot: usize) {
+        self.charsets.gl = slot;
+    }
+
+    /// SS2 and SS3
+    pub(super) fn single_shift(&mut self, slot: usize) {
+        self.charsets.single_shift = Some(slot);
+    }
+}
@@ -0,0 +1,240 @@
+//! Cursor movement and the erase / insert / delete control functions
+
+use super::{Mode, Screen};
+
+impl Screen {
+    /// Move to an absolute position, clamped to the screen
+    pub(super) fn goto(&mut self, row: usize, col: usize) {
+        let (rows, cols) = self.size();
+
+        self.cursor.row = row.min(rows - 1);
+        self.cursor.col = col.min(cols - 1);
+        self.cursor.pending_wrap = false;
+    }
+
+    pub(super) fn goto_row(&mut self, row: usize) {
+        self.goto(row, self.cursor.col);
+    }
+
+    pub(super) fn goto_col(&mut self, col: usize) {
+        self.goto(self.cursor.row, col);
+    }
+
+    /// A row as addressed by CUP / VPA: relative to, and kept within, the margins under DECOM
+    pub(super) fn origin_row(&self, row: usize) -> usize {
+        match self.mode(Mode::Origin) {
+            true => (self.margins.top + row).min(self.margins.bottom),
+            false => row,
+        }
+    }
+
+    pub(super) fn origin_col(&self, col: usize) -> usize {
+        match self.mode(Mode::Origin) {
+            true => (self.margins.left + col).min(self.margins.right),
+            false => col,
+        }
+    }
+
+    /// The top left corner, which is that of the margins under DECOM
+    pub(super) fn home(&mut self) {
+        self.goto(self.origin_row(0), self.origin_col(0));
+    }
+
+    /// CUU. Stops at the top margin when starting below it.
+    pub(super) fn cursor_up(&mut self, n: usize) {
+        let top = match self.cursor.row >= self.margins.top {
+            true => self.margins.top,
+            false => 0,
+        };
+
+        self.goto_row(self.cursor.row.saturating_sub(n).max(top));
+    }
+
+    /// CUD
+    pub(super) fn cursor_down(&mut self, n: usize) {
+        let bottom = match self.cursor.row <= self.margins.bottom {
+            true => self.margins.bottom,
+            false => self.grid.len() - 1,
+        };
+
+        self.goto_row(self.cursor.row.saturating_add(n).min(bottom));
+    }
+
+    /// CUF
+    pub(super) fn cursor_forward(&mut self, n: usize) {
+        let right = match self.cursor.col <= self.margins.right {
+            true => self.margins.right,
+            false => self.grid.cols() - 1,
+        };
+
+        self.goto_col(self.cursor.col.saturating_add(n).min(right));
+    }
+
+    /// CUB
+    pub(super) fn cursor_back(&mut self, n: usize) {
+        let left = match self.cursor.col >= self.margins.left {
+            true => self.margins.left,
+            false => 0,
+        };
+
+        self.goto_col(self.cursor.col.saturating_sub(n).max(left));
+    }
+
+    /// BS. With reverse wraparound (and autowrap), backing up from the left margin continues
+    /// from the end of the row above.
+    pub(super) fn backspace(&mut self) {
+        let wraps_back = self.mode(Mode::ReverseWrap) && self.mode(Mode::Autowrap);
+        let at_left = self.cursor.col == self.margins.left || self.cursor.col == 0;
+
+        match wraps_back && at_left && !self.cursor.pending_wrap && self.cursor.row > 0 {
+            true => {
+                let right = match self.cursor.col <= self.margins.right {
+                    true => self.margins.right,
+                    false => self.grid.cols() - 1,
+                };
+                self.goto(self.cursor.row - 1, right);
+            }
+            false => self.cursor_back(1),
+        }
+    }
+
+    /// CR: to the left margin, or the first column when already left of it
+    pub(super) fn carriage_return(&mut self) {
+        match self.cursor.col >= self.margins.left {
+            true => self.goto_col(self.margins.left),
+            false => self.goto_col(0),
+        }
+    }
+
+    /// IND: down a line, scrolling at the bottom margin
+    pub(super) fn index(&mut self) {
+        self.cursor.pending_wrap = false;
+
+        if self.cursor.row == self.margins.bottom {
+            if self.margins.contains_col(self.cursor.col) {
+                self.scroll_up(1);
+            }
+        } else if self.cursor.row + 1 < self.grid.len() {
+            self.cursor.row += 1;
+        }
+    }
+
+    /// RI: up a line, scrolling down at the top margin
+    pub(super) fn reverse_index(&mut self) {
+        self.cursor.pending_wrap = false;
+
+        if self.cursor.row == self.margins.top {
+            if self.margins.contains_col(self.cursor.col) {
+                self.scroll_down(1);
+            }
+        } else if self.cursor.row > 0 {
+            self.cursor.row -= 1;
+        }
+    }
+
+    /// NEL
+    pub(super) fn next_line(&mut self) {
+        self.carriage_return();
+        self.index();
+    }
+
+    /// ED
+    pub(super) fn erase_display(&mut self, mode: u16) {
+        let row = self.cursor.row;
+
+        let rows = match mode {
+            0 => {
+                self.erase_line(0);
+                row + 1..self.grid.len()
+            }
+            1 => {
+                self.erase_line(1);
+                0..row
+            }
+            2 => 0..self.grid.len(),
+            3 => {
+                self.scrollback.clear();
+                return;
+            }
+            _ => return,
+        };
+
+        for row in rows {
+            self.grid.row_mut(row).clear(&self.cursor.style);
+        }
+    }
+
+    /// EL
+    pub(super) fn erase_line(&mut self, mode: u16) {
+        let (cols, col) = (self.grid.cols(), self.cursor.col);
+
+        let range = match mode {
+            0 => col..cols,
+            1 => 0..col + 1,
+            2 => 0..cols,
+            _ => return,
+        };
+
+        self.cursor.pending_wrap = false;
+        // Unwrapped first, so erasing drops the spacer an early wrap left in the last column
+        let row = self.grid.row_mut(self.cursor.row);
+        row.set_wrapped(false);
+        row.erase(range, &self.cursor.style);
+    }
+
+    /// ECH
+    pub(super) fn erase_chars(&mut self, n: usize) {
+        let (cols, col) = (self.grid.cols(), self.cursor.col);
+
+        self.cursor.pending_wrap = false;
+        self.grid
+            .row_mut(self.cursor.row)
+            .erase(col..col.saturating_add(n).min(cols), &self.cursor.style);
+    }
+
+    /// ICH, within the left / right margins
+    pub(super) fn insert_chars(&mut self, n: usize) {
+        let col = self.cursor.col;
+
+        self.cursor.pending_wrap = false;
+        if self.margins.contains_col(col) {
+            self.grid.row_mut(self.cursor.row).insert(
+                col..self.margins.right + 1,
+                n,
+                &self.cursor.style,
+            );
+        }
+    }
+
+    /// DCH, within the left / right margins
+    pub(super) fn delete_chars(&mut self, n: usize) {
+        let col = self.cursor.col;
+
+        self.cursor.pending_wrap = false;
+        if self.margins.contains_col(col) {
+            self.grid.row_mut(self.cursor.row).delete(
+                col..self.margins.right + 1,
+                n,
+                &self.cursor.style,
+            );
+        }
+    }
+
+    /// IL. Only inside the scroll region, which the lines below the cursor move down within.
+    pub(super) fn insert_lines(&mut self, n: usize) {
+        if self.margins.contains_row(self.cursor.row) && self.margins.contains_col(self.cursor.col)
+        {
+            self.shift_down(self.cursor.row, n);
+            self.goto_col(self.margins.left);
+        }
+    }
+
+    /// DL
+    pub(super) fn delete_lines(&mut self, n: usize) {
+        if self.margins.contains_row(self.cursor.row) && self.margins.contains_col(self.cursor.col)
+        {
+            self.shift_up(self.cursor.row, n);
+            self.goto_col(self.margins.left);
+        }
+    }
+}
@@ -0,0 +1,286 @@
+use std::{iter, mem, ops::Range};
+
+use super::cell::{Cell, Style};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Row {
+    cells: Vec<Cell>,
+    /// The line carried on to the next row because it ran out of columns, rather than ending in
+    /// a newline
+    wrapped: bool,
+}
+
+impl Row {
+    pub(crate) fn new(cols: usize, style: &Style) -> Self {
+        Self {
+            cells: vec![Cell::blank(style); cols],
+            wrapped: false,
+        }
+    }
+
+    /// `cells`, padded out or cut down to `cols`
+    pub(crate) fn from_cells(mut cells: Vec<Cell>, cols: usize, wrapped: bool) -> Self {
+        cells.resize(cols, Cell::default());
+
+        let mut row = Self { cells, wrapped };
+        row.repair();
+        row
+    }
+
+    /// The cells of a wrapped row, without the spacer left when a wide character didn't fit
+    pub(crate) fn into_cells(mut self) -> Vec<Cell> {
+        let padded = match &self.cells[..] {
+            [.., before, last] => last.is_spacer() && !before.is_wide(),
+            [last] => last.is_spacer(),
+            [] => false,
+        };
+
+        if self.wrapped && padded {
+            self.cells.pop();
+        }
+
+        self.cells
+    }
+
+    pub fn cells(&self) -> &[Cell] {
+        &self.cells
+    }
+
+    pub fn is_wrapped(&self) -> bool {
+        self.wrapped
+    }
+
+    pub(crate) fn set_wrapped(&mut self, wrapped: bool) {
+        self.wrapped = wrapped;
+    }
+
+    /// Whether there's nothing on the row but blanks
+    pub fn is_blank(&self) -> bool {
+        !self.wrapped && self.cells.iter().all(Cell::is_blank)
+    }
+
+    /// The row's text without trailing blanks
+    pub fn text(&self) -> String {
+        let end = self
+            .cells
+            .iter()
+            .rposition(|cell| !cell.is_blank() && !cell.is_spacer())
+            .map_or(0, |i| i + 1);
+
+        self.cells[..end].iter().map(Cell::grapheme).collect()
+    }
+
+    /// Roughly how much memory the row takes up
+    pub(crate) fn size(&self) -> usize {
+        mem::size_of::<Self>() + self.cells.capacity() * mem::size_of::<Cell>()
+    }
+
+    pub(crate) fn resize(&mut self, cols: usize, style: &Style) {
+        self.cells.resize(cols, Cell::blank(style));
+        self.repair();
+    }
+
+    /// Write `cell` at `col`, along with its spacer when it's wide. Wide characters it partly
+    /// covers are erased.
+    pub(crate) fn put(&mut self, col: usize, cell: Cell) {
+        self.split_wide(col);
+
+        if cell.is_wide() {
+            self.split_wide(col + 1);
+            self.cells[col + 1] = Cell::spacer(&cell.style);
+        }
+
+        self.cells[col] = cell;
+    }
+
+    /// Erase both halves of the wide character at `col`, if there is one
+    fn split_wide(&mut self, col: usize) {
+        let start = match self.cells[col].width() {
+            // Not the padding spacer a wrapped row can end in
+            0 if col > 0 && self.cells[col - 1].is_wide() => col - 1,
+            2 => col,
+            _ => return,
+        };
+
+        for col in start..(start + 2).min(self.cells.len()) {
+            self.cells[col] = Cell::blank(&self.cells[col].style);
+        }
+    }
+
+    /// Erase halves of wide characters left without the other, after cells have moved around.
+    /// A spacer is also left at the end of a wrapped row when a wide character didn't fit.
+    fn repair(&mut self) {
+        let last = self.cells.len().saturating_sub(1);
+
+        for col in 0..self.cells.len() {
+            let orphaned = match self.cells[col].width() {
+                0 if self.wrapped && col == last => false,
+                0 => col == 0 || !self.cells[col - 1].is_wide(),
+                2 => !self.cells.get(col + 1).is_some_and(Cell::is_spacer),
+                _ => false,
+            };
+
+            if orphaned {
+                self.cells[col] = Cell::blank(&self.cells[col].style);
+            }
+        }
+    }
+
+    pub(crate) fn clear(&mut self, style: &Style) {
+        self.cells.fill(Cell::blank(style));
+        self.wrapped = false;
+    }
+
+    pub(crate) fn erase(&mut self, cols: Range<usize>, style: &Style) {
+        self.cells[cols].fill(Cell::blank(style));
+        self.repair();
+    }
+
+    /// Shift the cells in `cols` from `cols.start` right by `n`; those pushed past the end are lost
+    pub(crate) fn insert(&mut self, cols: Range<usize>, n: usize, style: &Style) {
+        let cells = &mut self.cells[cols];
+        let n = n.min(cells.len());
+
+        cells.rotate_right(n);
+        cells[..n].fill(Cell::blank(style));
+        self.repair();
+    }
+
+    /// Remove `n` cells from `cols.start`, pulling the rest of `cols` left
+    pub(crate) fn delete(&mut self, cols: Range<usize>, n: usize, style: &Style) {
+        let cells = &mut self.cells[cols];
+        let n = n.min(cells.len());
+        let end = cells.len() - n;
+
+        cells.rotate_left(n);
+        cells[end..].fill(Cell::blank(style));
+        self.repair();
+    }
+}
+
+impl std::ops::Index<usize> for Row {
+    type Output = Cell;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.cells[index]
+    }
+}
+
+impl std::ops::IndexMut<usize> for Row {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        &mut self.cells[index]
+    }
+}
+
+/// The visible rows of a screen
+#[derive(Clone, Debug)]
+pub(crate) struct Grid {
+    rows: Vec<Row>,
+    cols: usize,
+}
+
+impl Grid {
+    pub fn new(rows: usize, cols: usize) -> Self {
+        Self {
+            rows: vec![Row::new(cols, &Style::default()); rows],
+            cols,
+        }
+    }
+
+    pub fn from_rows(rows: Vec<Row>, cols: usize) -> Self {
+        Self { rows, cols }
+    }
+
+    pub fn take_rows(&mut self) -> Vec<Row> {
+        mem::take(&mut self.rows)
+    }
+
+    pub fn rows(&self) -> &[Row] {
+        &self.rows
+    }
+
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn row_mut(&mut self, row: usize) -> &mut Row {
+        &mut self.rows[row]
+    }
+
+    /// Move rows `top..=bottom` up by `n`, filling in blanks at the bottom. Returns the rows that
+    /// fell off the top.
+    pub fn scroll_up(&mut self, top: usize, bottom: usize, n: usize, style: &Style) -> Vec<Row> {
+        let n = n.min(bottom + 1 - top);
+        let scrolled = self.rows.drain(top..top + n).collect();
+
+        let at = bottom + 1 - n;
+        let blank = Row::new(self.cols, style);
+        self.rows.splice(at..at, iter::repeat_n(blank, n));
+
+        scrolled
+    }
+
+    /// Move rows `top..=bottom` down by `n`, filling in blanks at the top
+    pub fn scroll_down(&mut self, top: usize, bottom: usize, n: usize, style: &Style) {
+        let n = n.min(bottom + 1 - top);
+        self.rows.drain(bottom + 1 - n..=bottom);
+
+        let blank = Row::new(self.cols, style);
+        self.rows.splice(top..top, iter::repeat_n(blank, n));
+    }
+
+    /// Like [`Grid::scroll_up`] but only moving the cells in `cols`
+    pub fn scroll_cols_up(
+        &mut self,
+        top: usize,
+        bottom: usize,
+        cols: Range<usize>,
+        n: usize,
+        style: &Style,
+    ) {
+        for row in top..=bottom {
+            match row + n <= bottom {
+                true => {
+                    let source = self.rows[row + n].cells[cols.clone()].to_vec();
+                    self.rows[row].cells[cols.clone()].clone_from_slice(&source);
+                }
+                false => self.rows[row].erase(cols.clone(), style),
+            }
+        }
+    }
+
+    /// Like [`Grid::scroll_down`] but only moving the cells in `cols`
+    pub fn scroll_cols_down(
+        &mut self,
+        top: usize,
+        bottom: usize,
+        cols: Range<usize>,
+        n: usize,
+        style: &Style,
+    ) {
+        for row in (top..=bottom).rev() {
+            match row >= top + n {
+                true => {
+                    let source = self.rows[row - n].cells[cols.clone()].to_vec();
+                    self.rows[row].cells[cols.clone()].clone_from_slice(&source);
+                }
+                false => self.rows[row].erase(cols.clone(), style),
+            }
+        }
+    }
+
+    pub fn resize(&mut self, rows: usize, cols: usize) {
+        let style = Style::default();
+
+        for row in &mut self.rows {
+            row.resize(cols, &style);
+        }
+
+        self.rows.resize(rows, Row::new(cols, &style));
+        self.cols = cols;
+    }
+}
@@ -0,0 +1,404 @@
+//! An in-memory model of what a terminal would be showing, fed by the output of a child.
+//!
+//! ```no_run
+//! # use std::{io, process::Command};
+//! # use termu::{screen::Screen, Terminal};
+//! let mut terminal = Terminal::builder(Command::new("ls")).size(24, 80).build()?;
+//! let mut screen = Screen::new(24, 80);
+//!
+//! io::copy(terminal.stdout.as_mut().unwrap(), &mut screen)?;
+//! println!("{}", screen.contents());
+//! # Ok::<_, io::Error>(())
+//! ```
+
+use std::{io, mem};
+
+use crate::parser::{Params, Parser, Perform};
+
+mod buffer;
+mod cell;
+mod charset;
+mod edit;
+mod grid;
+mod modes;
+mod reflow;
+mod scroll;
+mod scrollback;
+mod sgr;
+mod tabs;
+mod width;
+
+pub use buffer::Buffer;
+pub use cell::{Attrs, Cell, Color, Grapheme, Style, Underline};
+pub use grid::Row;
+pub use modes::Mode;
+pub use scrollback::{Scrollback, DEFAULT_SCROLLBACK_LINES};
+pub use width::UnicodeVersion;
+
+use buffer::{Inactive, SavedCursor};
+use charset::Charsets;
+use grid::Grid;
+use modes::ModeFlags;
+use scroll::Margins;
+use tabs::TabStops;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Cursor {
+    pub row: usize,
+    pub col: usize,
+    /// The last column was just written; the next character wraps first
+    pub pending_wrap: bool,
+    /// What the next printed character is drawn with
+    pub style: Style,
+}
+
+pub struct Screen {
+    parser: Parser,
+    grid: Grid,
+    cursor: Cursor,
+    scrollback: Scrollback,
+    margins: Margins,
+    modes: ModeFlags,
+    buffer: Buffer,
+    inactive: Inactive,
+    saved_cursor: Option<SavedCursor>,
+    unicode: UnicodeVersion,
+    tabs: TabStops,
+    charsets: Charsets,
+    responses: Vec<u8>,
+}
+
+impl Screen {
+    pub fn new(rows: usize, cols: usize) -> Self {
+        let (rows, cols) = (rows.max(1), cols.max(1));
+
+        Self {
+            parser: Parser::new(),
+            grid: Grid::new(rows, cols),
+            cursor: Cursor::default(),
+            scrollback: Scrollback::default(),
+            margins: Margins::new(rows, cols),
+            modes: ModeFlags::default(),
+            buffer: Buffer::Primary,
+            inactive: Inactive::new(rows, cols),
+            saved_cursor: None,
+            unicode: UnicodeVersion::default(),
+            tabs: TabStops::new(cols),
+            charsets: Charsets::default(),
+            responses: Vec::new(),
+        }
+    }
+
+    pub fn process(&mut self, bytes: &[u8]) {
+        let mut parser = mem::take(&mut self.parser);
+        parser.advance(self, bytes);
+        self.parser = parser;
+    }
+
+    /// `(rows, cols)`
+    pub fn size(&self) -> (usize, usize) {
+        (self.grid.len(), self.grid.cols())
+    }
+
+    /// Change the size of the screen. Soft-wrapped lines on the primary screen and in the
+    /// scrollback are wrapped again at the new width; the alternate screen is just cut down or
+    /// padded out.
+    pub fn resize(&mut self, rows: usize, cols: usize) {
+        let (rows, cols) = (rows.max(1), cols.max(1));
+        let mut cursor = (self.cursor.row, self.cursor.col);
+
+        match self.buffer {
+            Buffer::Primary => {
+                reflow::reflow(
+                    &mut self.grid,
+                    &mut self.scrollback,
+                    rows,
+                    cols,
+                    &mut cursor,
+                );
+                self.inactive.grid.resize(rows, cols);
+            }
+            Buffer::Alternate => {
+                // The primary cursor saved on the way in (1049) follows the text too
+                let saved = self
+                    .inactive
+                    .saved_cursor
+                    .as_mut()
+                    .map(|saved| &mut saved.cursor);
+                let mut primary = saved.as_ref().map_or((0, 0), |c| (c.row, c.col));
+
+                reflow::reflow(
+                    &mut self.inactive.grid,
+                    &mut self.scrollback,
+                    rows,
+                    cols,
+                    &mut primary,
+                );
+                if let Some(saved) = saved {
+                    (saved.row, saved.col) = primary;
+                }
+
+                self.grid.resize(rows, cols);
+            }
+        }
+
+        self.margins = Margins::new(rows, cols);
+        self.tabs.resize(cols);
+        self.goto(cursor.0, cursor.1);
+    }
+
+    pub fn unicode_version(&self) -> UnicodeVersion {
+        self.unicode
+    }
+
+    /// Takes effect for characters printed from now on
+    pub fn set_unicode_version(&mut self, version: UnicodeVersion) {
+        self.unicode = version;
+    }
+
+    pub fn cursor(&self) -> &Cursor {
+        &self.cursor
+    }
+
+    pub fn scrollback(&self) -> &Scrollback {
+        &self.scrollback
+    }
+
+    /// To change the limits or clear the history
+    pub fn scrollback_mut(&mut self) -> &mut Scrollback {
+        &mut self.scrollback
+    }
+
+    pub fn rows(&self) -> &[Row] {
+        self.grid.rows()
+    }
+
+    pub fn row(&self, row: usize) -> Option<&Row> {
+        self.grid.rows().get(row)
+    }
+
+    pub fn cell(&self, row: usize, col: usize) -> Option<&Cell> {
+        self.row(row)?.cells().get(col)
+    }
+
+    /// The visible text, one line per row, without trailing blanks
+    pub fn contents(&self) -> String {
+        let lines = self.rows().iter().map(Row::text).collect::<Vec<_>>();
+        let end = lines
+            .iter()
+            .rposition(|l| !l.is_empty())
+            .map_or(0, |i| i + 1);
+        lines[..end].join("\n")
+    }
+
+    /// The column text wraps after: the right margin, unless the cursor is already past it
+    fn right_edge(&self) -> usize {
+        match self.cursor.col <= self.margins.right {
+            true => self.margins.right,
+            false => self.grid.cols() - 1,
+        }
+    }
+
+    /// Continue on the next row, as with a pending wrap
+    fn wrap(&mut self) {
+        // Rows are only joined back up on reflow when they span the whole width
+        if self.margins.full_width(self.grid.cols()) {
+            self.grid.row_mut(self.cursor.row).set_wrapped(true);
+        }
+
+        self.carriage_return();
+        self.index();
+    }
+
+    /// Move past the character just written, which ends in column `last`
+    fn advance(&mut self, last: usize) {
+        match last == self.right_edge() {
+            // Without autowrap the next character overwrites the last column instead
+            true => {
+                self.cursor.col = last;
+                self.cursor.pending_wrap = self.mode(Mode::Autowrap);
+            }
+            false => self.cursor.col = last + 1,
+        }
+    }
+
+    /// Where the character before the cursor starts, which a combining mark would join
+    fn previous_cell(&self) -> Option<(usize, usize)> {
+        let Cursor { row, col, .. } = self.cursor;
+        let col = match self.cursor.pending_wrap {
+            true => col,
+            false => col.checked_sub(1)?,
+        };
+
+        match self.grid.rows()[row][col].is_spacer() {
+            true => Some((row, col.checked_sub(1)?)),
+            false => Some((row, col)),
+        }
+    }
+
+    fn combine(&mut self, row: usize, col: usize, c: char) {
+        let edge = match col <= self.margins.right {
+            true => self.margins.right,
+            false => self.grid.cols() - 1,
+        };
+
+        let cell = &mut self.grid.row_mut(row)[col];
+        cell.push(c);
+        let widens = !cell.is_wide() && self.unicode.widens(cell.grapheme(), c);
+
+        // Becoming wide takes the next column too, if there's room for it
+        if widens && col < edge {
+            let mut cell = cell.clone();
+            cell.set_width(2);
+            self.grid.row_mut(row).put(col, cell);
+
+            if !self.cursor.pending_wrap {
+                self.advance(col + 1);
+            }
+        }
+    }
+}
+
+impl Perform for Screen {
+    fn print(&mut self, c: char) {
+        let c = self.charsets.translate(c);
+        let width = self.unicode.width(c);
+
+        if let Some((row, col)) = self.previous_cell() {
+            if width::joins(self.grid.rows()[row][col].grapheme(), c, width) {
+                return self.combine(row, col, c);
+            }
+        }
+
+        if self.cursor.pending_wrap {
+            self.wrap();
+        }
+
+        // A wide character that doesn't fit moves to the next row whole. On a row marked wrapped
+        // it leaves a spacer behind that reflow knows to drop.
+        if width == 2 && self.cursor.col == self.right_edge() && self.mode(Mode::Autowrap) {
+            if self.margins.full_width(self.grid.cols()) {
+                let Cursor { row, col, .. } = self.cursor;
+                self.grid
+                    .row_mut(row)
+                    .put(col, Cell::spacer(&self.cursor.style));
+            }
+            self.wrap();
+        }
+
+        let Cursor { row, col, .. } = self.cursor;
+        let width = width.min(self.right_edge() + 1 - col).max(1);
+        self.grid
+            .row_mut(row)
+            .put(col, Cell::with_width(c, width, self.cursor.style));
+
+        self.advance(col + width - 1);
+    }
+
+    fn execute(&mut self, byte: u8) {
+        match byte {
+            0x08 => self.backspace(),
+            0x09 => self.tab_forward(1),
+            0x0a..=0x0c => self.index(),
+            0x0d => self.carriage_return(),
+            0x0e => self.invoke_gl(1),
+            0x0f => self.invoke_gl(0),
+            _ => {}
+        }
+    }
+
+    fn csi_dispatch(&mut self, params: &Params, intermediates: &[u8], ignore: bool, action: char) {
+        if ignore {
+            return;
+        }
+
+        // Counts default to 1, positions are 1-based
+        let n = |index| params.get(index).unwrap_or(1) as usize;
+        let mode = params.get(0).unwrap_or(0);
+
+        match (intermediates, action) {
+            ([], 'A') => self.cursor_up(n(0)),
+            ([], 'B') => self.cursor_down(n(0)),
+            ([], 'C') => self.cursor_forward(n(0)),
+            ([], 'D') => self.cursor_back(n(0)),
+            ([], 'E') => {
+                self.cursor_down(n(0));
+                self.goto_col(0);
+            }
+            ([], 'F') => {
+                self.cursor_up(n(0));
+                self.goto_col(0);
+            }
+            ([], 'I') => self.tab_forward(n(0)),
+            ([], 'Z') => self.tab_back(n(0)),
+            ([], 'g') => self.clear_tab_stops(mode),
+            ([], 'G') => self.goto_col(self.origin_col(n(0) - 1)),
+            ([], 'H' | 'f') => self.goto(self.origin_row(n(0) - 1), self.origin_col(n(1) - 1)),
+            ([], 'd') => self.goto_row(self.origin_row(n(0) - 1)),
+            ([] | [b'?'], 'J') => self.erase_display(mode),
+            ([] | [b'?'], 'K') => self.erase_line(mode),
+            ([], 'X') => self.erase_chars(n(0)),
+            ([], '@') => self.insert_chars(n(0)),
+            ([], 'P') => self.delete_chars(n(0)),
+            ([], 'L') => self.insert_lines(n(0)),
+            ([], 'M') => self.delete_lines(n(0)),
+            ([], 'S') => self.scroll_up(n(0)),
+            // With more parameters, this is the mouse highlight tracking reply
+            ([], 'T') if params.len() <= 1 => self.scroll_down(n(0)),
+            ([], 'r') => {
+                let rows = self.grid.len();
+                self.set_top_bottom_margins(n(0), params.get(1).map_or(rows, usize::from));
+            }
+            // SCOSC, unless DECLRMM makes this DECSLRM
+            ([], 's') if !self.mode(Mode::LeftRightMargins) => self.save_cursor(),
+            ([], 's') => {
+                let cols = self.grid.cols();
+                self.set_left_right_margins(n(0), params.get(1).map_or(cols, usize::from));
+            }
+            ([], 'u') => self.restore_cursor(),
+            ([], 'm') => self.cursor.style.sgr(params),
+            ([b'?'], 'h' | 'l') => {
+                for mode in params {
+                    self.set_private_mode(mode[0], action == 'h');
+                }
+            }
+            ([b'?', b'$'], 'p') => self.report_private_mode(mode),
+            ([b'$'], 'p') => self.report_mode(mode),
+            _ => {}
+        }
+    }
+
+    fn esc_dispatch(&mut self, intermediates: &[u8], ignore: bool, byte: u8) {
+        if ignore {
+            return;
+        }
+
+        match (intermediates, byte) {
+            ([], b'D') => self.index(),
+            ([], b'E') => self.next_line(),
+            ([], b'M') => self.reverse_index(),
+            ([], b'H') => self.set_tab_stop(),
+            ([], b'N') => self.single_shift(2),
+            ([], b'O') => self.single_shift(3),
+            ([], b'n') => self.invoke_gl(2),
+            ([], b'o') => self.invoke_gl(3),
+            // LS1R / LS2R / LS3R only matter to 8-bit input, which never gets past UTF-8 decoding
+            ([], b'~' | b'}' | b'|') => {}
+            ([b'(' | b')' | b'*' | b'+', ..], _) => self.designate_charset(intermediates, byte),
+            ([], b'7') => self.save_cursor(),
+            ([], b'8') => self.restore_cursor(),
+            _ => {}
+        }
+    }
+}
+
+impl io::Write for Screen {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.process(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,171 @@
+//! DEC private modes (DECSET / DECRST), kept in one table that DECRQM is answered from
+
+use bitflags::bitflags;
+
+use super::Screen;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Mode {
+    /// DECCKM: cursor keys send application sequences (`ESC O A` rather than `ESC [ A`)
+    CursorKeys,
+    /// DECSCNM: the whole screen is shown in reverse video
+    ReverseVideo,
+    /// DECOM: cursor addressing is relative to the margins
+    Origin,
+    /// DECAWM: printing past the right margin continues on the next row
+    Autowrap,
+    /// DECTCEM
+    CursorVisible,
+    /// Backspace at the left margin moves to the end of the row above
+    ReverseWrap,
+    /// DECLRMM: DECSLRM may set left / right margins
+    LeftRightMargins,
+    /// The application wants focus in / out reports
+    FocusEvents,
+    /// Pasted text should be wrapped in `ESC [ 200 ~` / `ESC [ 201 ~`
+    BracketedPaste,
+    /// The application is partway through drawing a frame
+    SynchronizedOutput,
+}
+
+/// Each mode and the number DECSET / DECRST / DECRQM know it by
+const MODES: &[(Mode, u16, ModeFlags)] = &[
+    (Mode::CursorKeys, 1, ModeFlags::CURSOR_KEYS),
+    (Mode::ReverseVideo, 5, ModeFlags::REVERSE_VIDEO),
+    (Mode::Origin, 6, ModeFlags::ORIGIN),
+    (Mode::Autowrap, 7, ModeFlags::AUTOWRAP),
+    (Mode::CursorVisible, 25, ModeFlags::CURSOR_VISIBLE),
+    (Mode::ReverseWrap, 45, ModeFlags::REVERSE_WRAP),
+    (Mode::LeftRightMargins, 69, ModeFlags::LEFT_RIGHT_MARGINS),
+    (Mode::FocusEvents, 1004, ModeFlags::FOCUS_EVENTS),
+    (Mode::BracketedPaste, 2004, ModeFlags::BRACKETED_PASTE),
+    (
+        Mode::SynchronizedOutput,
+        2026,
+        ModeFlags::SYNCHRONIZED_OUTPUT,
+    ),
+];
+
+impl Mode {
+    pub fn from_number(number: u16) -> Option<Self> {
+        MODES
+            .iter()
+            .find(|(_, n, _)| *n == number)
+            .map(|(mode, _, _)| *mode)
+    }
+
+    /// What DECSET / DECRST call it, e.g. 2004 for bracketed paste
+    pub fn number(self) -> u16 {
+        self.entry().1
+    }
+
+    fn flag(self) -> ModeFlags {
+        self.entry().2
+    }
+
+    fn entry(self) -> &'static (Mode, u16, ModeFlags) {
+        // Every mode has an entry
+        MODES.iter().find(|(mode, _, _)| *mode == self).unwrap()
+    }
+}
+
+bitflags! {
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub(super) struct ModeFlags: u16 {
+        const CURSOR_KEYS = 1 << 0;
+        const REVERSE_VIDEO = 1 << 1;
+        const ORIGIN = 1 << 2;
+        const AUTOWRAP = 1 << 3;
+        const CURSOR_VISIBLE = 1 << 4;
+        const REVERSE_WRAP = 1 << 5;
+        const LEFT_RIGHT_MARGINS = 1 << 6;
+        const FOCUS_EVENTS = 1 << 7;
+        const BRACKETED_PASTE = 1 << 8;
+        const SYNCHRONIZED_OUTPUT = 1 << 9;
+    }
+}
+
+impl Default for ModeFlags {
+    fn default() -> Self {
+        Self::AUTOWRAP | Self::CURSOR_VISIBLE
+    }
+}
+
+/// DECRQM's answer for a mode
+#[derive(Clone, Copy)]
+enum ModeState {
+    NotRecognized = 0,
+    Set = 1,
+    Reset = 2,
+}
+
+impl Screen {
+    pub fn mode(&self, mode: Mode) -> bool {
+        self.modes.contains(mode.flag())
+    }
+
+    /// As if the application had sent DECSET / DECRST
+    pub fn set_mode(&mut self, mode: Mode, enabled: bool) {
+        self.modes.set(mode.flag(), enabled);
+
+        match mode {
+            Mode::Origin => self.home(),
+            Mode::LeftRightMargins if !enabled => {
+                self.margins.left = 0;
+                self.margins.right = self.grid.cols() - 1;
+            }
+            Mode::Autowrap if !enabled => self.cursor.pending_wrap = false,
+            _ => {}
+        }
+    }
+
+    /// Bytes the screen wants sent back to the application, e.g. replies to DECRQM. Write
+    /// these to the terminal's stdin.
+    pub fn take_responses(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.responses)
+    }
+
+    /// DECSET / DECRST
+    pub(super) fn set_private_mode(&mut self, number: u16, enabled: bool) {
+        match number {
+            47 | 1047 | 1049 => self.set_alternate_mode(number, enabled),
+            1048 => match enabled {
+                true => self.save_cursor(),
+                false => self.restore_cursor(),
+            },
+            number => {
+                if let Some(mode) = Mode::from_number(number) {
+                    self.set_mode(mode, enabled);
+                }
+            }
+        }
+    }
+
+    /// DECRQM for a private mode
+    pub(super) fn report_private_mode(&mut self, number: u16) {
+        let state = match number {
+            47 | 1047 | 1049 => Some(self.is_alternate()),
+            number => Mode::from_number(number).map(|mode| self.mode(mode)),
+        };
+
+        let state = match state {
+            Some(true) => ModeState::Set,
+            Some(false) => ModeState::Reset,
+            None => ModeState::NotRecognized,
+        };
+
+        self.respond(format!("\x1b[?{number};{}$y", state as u8));
+    }
+
+    /// DECRQM for an ANSI mode, none of which are tracked
+    pub(super) fn report_mode(&mut self, number: u16) {
+        self.respond(format!(
+            "\x1b[{number};{}$y",
+            ModeState::NotRecognized as u8
+        ));
+    }
+
+    fn respond(&mut self, response: String) {
+        self.responses.extend_from_slice(response.as_bytes());
+    }
+}
@@ -0,0 +1,119 @@
+//! Laying rows out again at a new width, joining soft-wrapped rows back into lines first
+
+use std::mem;
+
+use super::{
+    cell::{Cell, Style},
+    grid::{Grid, Row},
+    scrollback::Scrollback,
+};
+
+/// Reflow the primary screen and its history to `rows` x `cols`. `cursor` is the `(row, col)`
+/// on the screen, moved to wherever that cell ends up.
+pub(super) fn reflow(
+    grid: &mut Grid,
+    scrollback: &mut Scrollback,
+    rows: usize,
+    cols: usize,
+    cursor: &mut (usize, usize),
+) {
+    let history = scrollback.take();
+    let offset = history.len();
+
+    // Blank rows below both the text and the cursor would otherwise end up in the scrollback
+    let used = grid
+        .rows()
+        .iter()
+        .rposition(|row| !row.is_blank())
+        .map_or(0, |i| i + 1)
+        .max(cursor.0 + 1);
+
+    let lines = history
+        .into_iter()
+        .chain(grid.take_rows().into_iter().take(used));
+    let mut position = (cursor.0 + offset, cursor.1);
+    let mut laid_out = rewrap(lines, cols, &mut position);
+
+    // Keep the bottom of the output in view, unless that would hide the cursor
+    let start = laid_out.len().saturating_sub(rows).min(position.0);
+    let mut visible = laid_out.split_off(start);
+    visible.resize(rows, Row::new(cols, &Style::default()));
+
+    scrollback.extend(laid_out);
+    *grid = Grid::from_rows(visible, cols);
+    *cursor = (position.0 - start, position.1);
+}
+
+fn rewrap(rows: impl Iterator<Item = Row>, cols: usize, cursor: &mut (usize, usize)) -> Vec<Row> {
+    let mut laid_out = Vec::new();
+    let mut line = Vec::new();
+    let mut cursor_offset = None;
+    let mut moved = *cursor;
+
+    for (i, row) in rows.enumerate() {
+        if i == cursor.0 {
+            cursor_offset = Some(line.len() + cursor.1);
+        }
+
+        let wrapped = row.is_wrapped();
+        line.extend(row.into_cells());
+
+        if !wrapped {
+            wrap_line(
+                &mut laid_out,
+                &mut line,
+                cols,
+                cursor_offset.take(),
+                &mut moved,
+            );
+        }
+    }
+
+    if !line.is_empty() {
+        wrap_line(&mut laid_out, &mut line, cols, cursor_offset, &mut moved);
+    }
+
+    *cursor = moved;
+    laid_out
+}
+
+/// Split one logical line into rows of `cols`, all but the last marked as wrapped
+fn wrap_line(
+    laid_out: &mut Vec<Row>,
+    line: &mut Vec<Cell>,
+    cols: usize,
+    cursor_offset: Option<usize>,
+    cursor: &mut (usize, usize),
+) {
+    // Trailing blanks only padded the line out to the old width
+    let mut end = line
+        .iter()
+        .rposition(|cell| *cell != Cell::default())
+        .map_or(0, |i| i + 1);
+
+    if let Some(offset) = cursor_offset {
+        end = end.max(offset + 1);
+    }
+
+    line.truncate(end);
+
+    let mut row = Vec::with_capacity(cols);
+    for (i, cell) in line.drain(..).enumerate() {
+        // Wide characters move down whole rather than being split over two rows
+        if !cell.is_spacer() && !row.is_empty() && row.len() + cell.width() > cols {
+            if row.len() < cols {
+                row.push(Cell::spacer(&Style::default()));
+            }
+
+            laid_out.push(Row::from_cells(mem::take(&mut row), cols, true));
+        }
+
+        if cursor_offset == Some(i) {
+            *cursor = (laid_out.len(), row.len());
+        }
+
+        row.push(cell);
+    }
+
+    laid_out.push(Row::from_cells(row, cols, false));
+}
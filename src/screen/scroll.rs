@@ -0,0 +1,110 @@
+//! Scroll regions: top / bottom margins (DECSTBM) and left / right margins (DECSLRM)
+
+use std::ops::Range;
+
+use super::{Mode, Row, Screen};
+
+/// The scroll region, inclusive on all sides
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) struct Margins {
+    pub top: usize,
+    pub bottom: usize,
+    pub left: usize,
+    pub right: usize,
+}
+
+impl Margins {
+    pub fn new(rows: usize, cols: usize) -> Self {
+        Self {
+            top: 0,
+            bottom: rows - 1,
+            left: 0,
+            right: cols - 1,
+        }
+    }
+
+    pub fn cols(&self) -> Range<usize> {
+        self.left..self.right + 1
+    }
+
+    pub fn contains_row(&self, row: usize) -> bool {
+        (self.top..=self.bottom).contains(&row)
+    }
+
+    pub fn contains_col(&self, col: usize) -> bool {
+        (self.left..=self.right).contains(&col)
+    }
+
+    pub fn full_width(&self, cols: usize) -> bool {
+        self.left == 0 && self.right == cols - 1
+    }
+}
+
+impl Screen {
+    /// DECSTBM, with 1-based rows
+    pub(super) fn set_top_bottom_margins(&mut self, top: usize, bottom: usize) {
+        let (top, bottom) = (top.max(1) - 1, bottom.min(self.grid.len()) - 1);
+
+        if top < bottom {
+            self.margins.top = top;
+            self.margins.bottom = bottom;
+            self.home();
+        }
+    }
+
+    /// DECSLRM, with 1-based columns. Only has an effect while DECLRMM is set.
+    pub(super) fn set_left_right_margins(&mut self, left: usize, right: usize) {
+        let (left, right) = (left.max(1) - 1, right.min(self.grid.cols()) - 1);
+
+        if self.mode(Mode::LeftRightMargins) && left < right {
+            self.margins.left = left;
+            self.margins.right = right;
+            self.home();
+        }
+    }
+
+    /// SU, and a linefeed at the bottom margin. Only rows scrolled off the whole primary screen are
+    /// kept in the scrollback.
+    pub(super) fn scroll_up(&mut self, n: usize) {
+        let Margins { top, bottom, .. } = self.margins;
+        let whole_screen = top == 0 && bottom == self.grid.len() - 1;
+
+        let scrolled = self.shift_up(top, n);
+        if whole_screen && !self.is_alternate() {
+            self.scrollback.extend(scrolled);
+        }
+    }
+
+    /// SD, and a reverse index at the top margin
+    pub(super) fn scroll_down(&mut self, n: usize) {
+        self.shift_down(self.margins.top, n);
+    }
+
+    /// Move rows `top..=bottom margin` up within the left / right margins, returning whole rows
+    /// that fell off
+    pub(super) fn shift_up(&mut self, top: usize, n: usize) -> Vec<Row> {
+        let Margins { bottom, .. } = self.margins;
+        let style = self.cursor.style;
+
+        match self.margins.full_width(self.grid.cols()) {
+            true => self.grid.scroll_up(top, bottom, n, &style),
+            false => {
+                self.grid
+                    .scroll_cols_up(top, bottom, self.margins.cols(), n, &style);
+                Vec::new()
+            }
+        }
+    }
+
+    pub(super) fn shift_down(&mut self, top: usize, n: usize) {
+        let Margins { bottom, .. } = self.margins;
+        let style = self.cursor.style;
+
+        match self.margins.full_width(self.grid.cols()) {
+            true => self.grid.scroll_down(top, bottom, n, &style),
+            false => self
+                .grid
+                .scroll_cols_down(top, bottom, self.margins.cols(), n, &style),
+        }
+    }
+}
@@ -0,0 +1,125 @@
+use std::{collections::VecDeque, mem, ops::Index};
+
+use super::grid::Row;
+
+/// How many rows a new screen keeps by default
+pub const DEFAULT_SCROLLBACK_LINES: usize = 10_000;
+
+/// Rows that scrolled off the top of the primary screen, oldest first. Once a limit is reached
+/// the oldest rows are dropped.
+#[derive(Clone, Debug)]
+pub struct Scrollback {
+    rows: VecDeque<Row>,
+    bytes: usize,
+    max_lines: Option<usize>,
+    max_bytes: Option<usize>,
+}
+
+impl Scrollback {
+    /// `None` leaves that dimension unbounded
+    pub fn new(max_lines: Option<usize>, max_bytes: Option<usize>) -> Self {
+        Self {
+            rows: VecDeque::new(),
+            bytes: 0,
+            max_lines,
+            max_bytes,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// Roughly how much memory the rows take up
+    pub fn bytes(&self) -> usize {
+        self.bytes
+    }
+
+    /// Index 0 is the oldest row
+    pub fn get(&self, index: usize) -> Option<&Row> {
+        self.rows.get(index)
+    }
+
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &Row> + ExactSizeIterator {
+        self.rows.iter()
+    }
+
+    pub fn clear(&mut self) {
+        self.rows.clear();
+        self.bytes = 0;
+    }
+
+    pub fn max_lines(&self) -> Option<usize> {
+        self.max_lines
+    }
+
+    pub fn set_max_lines(&mut self, max_lines: Option<usize>) {
+        self.max_lines = max_lines;
+        self.trim();
+    }
+
+    pub fn max_bytes(&self) -> Option<usize> {
+        self.max_bytes
+    }
+
+    pub fn set_max_bytes(&mut self, max_bytes: Option<usize>) {
+        self.max_bytes = max_bytes;
+        self.trim();
+    }
+
+    pub(crate) fn extend(&mut self, rows: impl IntoIterator<Item = Row>) {
+        for row in rows {
+            self.bytes += row.size();
+            self.rows.push_back(row);
+        }
+
+        self.trim();
+    }
+
+    /// Take every row out, e.g. to lay them out again at a new width
+    pub(crate) fn take(&mut self) -> VecDeque<Row> {
+        self.bytes = 0;
+        mem::take(&mut self.rows)
+    }
+
+    fn trim(&mut self) {
+        let over = |s: &Self| {
+            s.max_lines.is_some_and(|max| s.rows.len() > max)
+                || s.max_bytes.is_some_and(|max| s.bytes > max)
+        };
+
+        while over(self) {
+            match self.rows.pop_front() {
+                Some(row) => self.bytes -= row.size(),
+                None => break,
+            }
+        }
+    }
+}
+
+impl Default for Scrollback {
+    fn default() -> Self {
+        Self::new(Some(DEFAULT_SCROLLBACK_LINES), None)
+    }
+}
+
+impl Index<usize> for Scrollback {
+    type Output = Row;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.rows[index]
+    }
+}
+
+impl<'a> IntoIterator for &'a Scrollback {
+    type Item = &'a Row;
+    type IntoIter = std::collections::vec_deque::Iter<'a, Row>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.rows.iter()
+    }
+}
@@ -0,0 +1,100 @@
+use super::cell::{Attrs, Color, Style, Underline};
+use crate::parser::Params;
+
+impl Style {
+    /// Apply Select Graphic Rendition parameters
+    pub(crate) fn sgr(&mut self, params: &Params) {
+        if params.is_empty() {
+            *self = Style::default();
+            return;
+        }
+
+        let mut groups = params.iter();
+        while let Some(group) = groups.next() {
+            match *group {
+                [0, ..] => *self = Style::default(),
+                [1, ..] => self.attrs.insert(Attrs::BOLD),
+                [2, ..] => self.attrs.insert(Attrs::DIM),
+                [3, ..] => self.attrs.insert(Attrs::ITALIC),
+                [4] => self.underline = Underline::Single,
+                [4, style, ..] => self.underline = underline(style),
+                [5 | 6, ..] => self.attrs.insert(Attrs::BLINK),
+                [7, ..] => self.attrs.insert(Attrs::INVERSE),
+                [8, ..] => self.attrs.insert(Attrs::HIDDEN),
+                [9, ..] => self.attrs.insert(Attrs::STRIKETHROUGH),
+                [21, ..] => self.underline = Underline::Double,
+                [22, ..] => self.attrs.remove(Attrs::BOLD | Attrs::DIM),
+                [23, ..] => self.attrs.remove(Attrs::ITALIC),
+                [24, ..] => self.underline = Underline::None,
+                [25, ..] => self.attrs.remove(Attrs::BLINK),
+                [27, ..] => self.attrs.remove(Attrs::INVERSE),
+                [28, ..] => self.attrs.remove(Attrs::HIDDEN),
+                [29, ..] => self.attrs.remove(Attrs::STRIKETHROUGH),
+                [n @ 30..=37, ..] => self.fg = Color::Indexed(n as u8 - 30),
+                [38, ref sub @ ..] => {
+                    if let Some(color) = extended(sub, &mut groups) {
+                        self.fg = color;
+                    }
+                }
+                [39, ..] => self.fg = Color::Default,
+                [n @ 40..=47, ..] => self.bg = Color::Indexed(n as u8 - 40),
+                [48, ref sub @ ..] => {
+                    if let Some(color) = extended(sub, &mut groups) {
+                        self.bg = color;
+                    }
+                }
+                [49, ..] => self.bg = Color::Default,
+                [53, ..] => self.attrs.insert(Attrs::OVERLINE),
+                [55, ..] => self.attrs.remove(Attrs::OVERLINE),
+                [58, ref sub @ ..] => {
+                    if let Some(color) = extended(sub, &mut groups) {
+                        self.underline_color = color;
+                    }
+                }
+                [59, ..] => self.underline_color = Color::Default,
+                [n @ 90..=97, ..] => self.fg = Color::Indexed(n as u8 - 90 + 8),
+                [n @ 100..=107, ..] => self.bg = Color::Indexed(n as u8 - 100 + 8),
+                _ => {}
+            }
+        }
+    }
+}
+
+/// `4:x`
+fn underline(style: u16) -> Underline {
+    match style {
+        0 => Underline::None,
+        2 => Underline::Double,
+        3 => Underline::Curly,
+        4 => Underline::Dotted,
+        5 => Underline::Dashed,
+        _ => Underline::Single,
+    }
+}
+
+/// The colour following a 38 / 48 / 58, either as its subparameters (`38:2::r:g:b`, `38:5:n`) or
+/// in the older form spread over the next parameters (`38;2;r;g;b`, `38;5;n`)
+fn extended<'a>(sub: &[u16], groups: &mut impl Iterator<Item = &'a [u16]>) -> Option<Color> {
+    let component = |value: u16| u8::try_from(value).ok();
+
+    if !sub.is_empty() {
+        return match *sub {
+            [5, n, ..] => component(n).map(Color::Indexed),
+            // With a colour space id in front
+            [2, _, r, g, b, ..] | [2, r, g, b] => {
+                Some(Color::Rgb(component(r)?, component(g)?, component(b)?))
+            }
+            _ => None,
+        };
+    }
+
+    let mut next = || groups.next().and_then(|group| group.first().copied());
+    match next()? {
+        5 => component(next()?).map(Color::Indexed),
+        2 => {
+            let (r, g, b) = (next()?, next()?, next()?);
+            Some(Color::Rgb(component(r)?, component(g)?, component(b)?))
+        }
+        _ => None,
+    }
+}
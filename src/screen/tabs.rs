@@ -0,0 +1,102 @@
+//! Tab stops: HT, HTS, TBC, CHT and CBT
+
+use super::Screen;
+
+/// Where stops are until something sets or clears them
+const TAB_WIDTH: usize = 8;
+
+#[derive(Clone, Debug)]
+pub(super) struct TabStops(Vec<bool>);
+
+impl TabStops {
+    pub fn new(cols: usize) -> Self {
+        Self((0..cols).map(is_default).collect())
+    }
+
+    /// Stops already set are kept; new columns get the default ones
+    pub fn resize(&mut self, cols: usize) {
+        let old = self.0.len();
+        self.0.truncate(cols);
+        self.0.extend((old..cols).map(is_default));
+    }
+
+    pub fn set(&mut self, col: usize, stop: bool) {
+        if let Some(slot) = self.0.get_mut(col) {
+            *slot = stop;
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.0.fill(false);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.0
+            .iter()
+            .enumerate()
+            .filter(|(_, stop)| **stop)
+            .map(|(col, _)| col)
+    }
+
+    /// The first stop after `col`
+    fn next(&self, col: usize) -> Option<usize> {
+        self.iter().find(|stop| *stop > col)
+    }
+
+    /// The last stop before `col`
+    fn previous(&self, col: usize) -> Option<usize> {
+        self.iter().take_while(|stop| *stop < col).last()
+    }
+}
+
+fn is_default(col: usize) -> bool {
+    col > 0 && col.is_multiple_of(TAB_WIDTH)
+}
+
+impl Screen {
+    /// The columns with a tab stop set
+    pub fn tab_stops(&self) -> impl Iterator<Item = usize> + '_ {
+        self.tabs.iter()
+    }
+
+    /// HT / CHT: forward `n` stops, no further than the right margin (or edge)
+    pub(super) fn tab_forward(&mut self, n: usize) {
+        let right = self.right_edge();
+        let mut col = self.cursor.col;
+
+        for _ in 0..n {
+            col = self.tabs.next(col).unwrap_or(right).min(right);
+        }
+
+        self.goto_col(col);
+    }
+
+    /// CBT: back `n` stops, no further than the left margin (or edge)
+    pub(super) fn tab_back(&mut self, n: usize) {
+        let left = match self.cursor.col >= self.margins.left {
+            true => self.margins.left,
+            false => 0,
+        };
+        let mut col = self.cursor.col;
+
+        for _ in 0..n {
+            col = self.tabs.previous(col).unwrap_or(left).max(left);
+        }
+
+        self.goto_col(col);
+    }
+
+    /// HTS
+    pub(super) fn set_tab_stop(&mut self) {
+        self.tabs.set(self.cursor.col, true);
+    }
+
+    /// TBC
+    pub(super) fn clear_tab_stops(&mut self, mode: u16) {
+        match mode {
+            0 => self.tabs.set(self.cursor.col, false),
+            3 => self.tabs.clear(),
+            _ => {}
+        }
+    }
+}
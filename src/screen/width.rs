@@ -0,0 +1,153 @@
+//! How many columns characters take up
+
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// Which width tables to follow. Programs lay text out using whatever their own `wcwidth`
+/// says, so this should match the tables the child was built against.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum UnicodeVersion {
+    /// Emoji take one column, as in tables from before Unicode 9 (e.g. glibc before 2.26)
+    V8,
+    /// Emoji presentation characters take two columns, as in glibc since 2.26
+    #[default]
+    V9,
+    /// As `V9`, and U+FE0F (VS16) widens the text-presentation emoji before it, e.g. ❤️
+    Latest,
+}
+
+impl UnicodeVersion {
+    /// The columns `c` takes up on its own. Combining marks and the like are 0.
+    pub fn width(self, c: char) -> usize {
+        // Controls never make it into the grid
+        let width = c.width().unwrap_or(1);
+
+        match self {
+            Self::V8 if width == 2 && is_emoji_presentation(c) => 1,
+            _ => width,
+        }
+    }
+
+    /// Whether `c`, just appended to a single-column `grapheme`, makes it two columns wide
+    pub(crate) fn widens(self, grapheme: &str, c: char) -> bool {
+        let widened = match c {
+            // A flag is two regional indicators; each took a column, so the pair still does
+            '\u{1f1e6}'..='\u{1f1ff}' => true,
+            '\u{fe0f}' => self == Self::Latest,
+            _ => false,
+        };
+
+        widened && grapheme.width() == 2
+    }
+}
+
+/// Whether `c` continues the grapheme before it, given what that ends with
+pub(crate) fn joins(previous: &str, c: char, width: usize) -> bool {
+    let mut chars = previous.chars();
+    let last = chars.next_back();
+
+    match c {
+        _ if width == 0 => true,
+        _ if last == Some(ZWJ) => true,
+        // Skin tones
+        '\u{1f3fb}'..='\u{1f3ff}' => last.is_some_and(|last| last.width() == Some(2)),
+        // The second half of a flag
+        '\u{1f1e6}'..='\u{1f1ff}' => {
+            last.is_some_and(|last| ('\u{1f1e6}'..='\u{1f1ff}').contains(&last))
+                && chars.next_back().is_none()
+        }
+        _ => false,
+    }
+}
+
+const ZWJ: char = '\u{200d}';
+
+fn is_emoji_presentation(c: char) -> bool {
+    let c = c as u32;
+
+    EMOJI_PRESENTATION
+        .binary_search_by(|&(start, end)| match (start > c, end < c) {
+            (true, _) => std::cmp::Ordering::Greater,
+            (_, true) => std::cmp::Ordering::Less,
+            _ => std::cmp::Ordering::Equal,
+        })
+        .is_ok()
+}
+
+/// Wide characters with `Emoji_Presentation=Yes` outside the CJK blocks: the ones that only
+/// became wide in Unicode 9
+const EMOJI_PRESENTATION: &[(u32, u32)] = &[
+    (0x231a, 0x231b),
+    (0x23e9, 0x23ec),
+    (0x23f0, 0x23f0),
+    (0x23f3, 0x23f3),
+    (0x25fd, 0x25fe),
+    (0x2614, 0x2615),
+    (0x2648, 0x2653),
+    (0x267f, 0x267f),
+    (0x2693, 0x2693),
+    (0x26a1, 0x26a1),
+    (0x26aa, 0x26ab),
+    (0x26bd, 0x26be),
+    (0x26c4, 0x26c5),
+    (0x26ce, 0x26ce),
+    (0x26d4, 0x26d4),
+    (0x26ea, 0x26ea),
+    (0x26f2, 0x26f3),
+    (0x26f5, 0x26f5),
+    (0x26fa, 0x26fa),
+    (0x26fd, 0x26fd),
+    (0x2705, 0x2705),
+    (0x270a, 0x270b),
+    (0x2728, 0x2728),
+    (0x274c, 0x274c),
+    (0x274e, 0x274e),
+    (0x2753, 0x2755),
+    (0x2757, 0x2757),
+    (0x2795, 0x2797),
+    (0x27b0, 0x27b0),
+    (0x27bf, 0x27bf),
+    (0x2b1b, 0x2b1c),
+    (0x2b50, 0x2b50),
+    (0x2b55, 0x2b55),
+    (0x1f004, 0x1f004),
+    (0x1f0cf, 0x1f0cf),
+    (0x1f18e, 0x1f18e),
+    (0x1f191, 0x1f19a),
+    (0x1f300, 0x1f320),
+    (0x1f32d, 0x1f335),
+    (0x1f337, 0x1f37c),
+    (0x1f37e, 0x1f393),
+    (0x1f3a0, 0x1f3ca),
+    (0x1f3cf, 0x1f3d3),
+    (0x1f3e0, 0x1f3f0),
+    (0x1f3f4, 0x1f3f4),
+    (0x1f3f8, 0x1f43e),
+    (0x1f440, 0x1f440),
+    (0x1f442, 0x1f4fc),
+    (0x1f4ff, 0x1f53d),
+    (0x1f54b, 0x1f54e),
+    (0x1f550, 0x1f567),
+    (0x1f57a, 0x1f57a),
+    (0x1f595, 0x1f596),
+    (0x1f5a4, 0x1f5a4),
+    (0x1f5fb, 0x1f64f),
+    (0x1f680, 0x1f6c5),
+    (0x1f6cc, 0x1f6cc),
+    (0x1f6d0, 0x1f6d2),
+    (0x1f6d5, 0x1f6d8),
+    (0x1f6dc, 0x1f6df),
+    (0x1f6eb, 0x1f6ec),
+    (0x1f6f4, 0x1f6fc),
+    (0x1f7e0, 0x1f7eb),
+    (0x1f7f0, 0x1f7f0),
+    (0x1f90c, 0x1f93a),
+    (0x1f93c, 0x1f945),
+    (0x1f947, 0x1f9ff),
+    (0x1fa70, 0x1fa7c),
+    (0x1fa80, 0x1fa8a),
+    (0x1fa8e, 0x1fac6),
+    (0x1fac8, 0x1fac8),
+    (0x1facd, 0x1fadc),
+    (0x1fadf, 0x1faea),
+    (0x1faef, 0x1faf8),
+];
@@ -0,0 +1,160 @@
+use nix::{
+    pty::Winsize,
+    sys::signal::{kill, killpg, Signal},
+    unistd::Pid,
+};
+use std::{
+    io::{self, Read, Write},
+    os::fd::{AsFd, AsRawFd, BorrowedFd, OwnedFd, RawFd},
+    sync::Arc,
+    time::Duration,
+};
+
+use crate::{
+    process::{foreground_group, no_child, signal_group, LivePid},
+    set_winsize, IoFd, Terminal,
+};
+
+/// The output half of a split [`Terminal`]
+pub struct PtyReader(IoFd);
+
+/// The input half of a split [`Terminal`]
+pub struct PtyWriter(IoFd);
+
+/// Resizing and signalling for a split [`Terminal`]. Cheap to clone; waiting on the child stays
+/// with the `Terminal` itself, and the pid follows whatever it reaps or attaches.
+#[derive(Clone)]
+pub struct PtyControl {
+    master: Arc<OwnedFd>,
+    pid: Arc<LivePid>,
+}
+
+// Each half owns its own descriptor onto the master, which stays open until the last is dropped
+const _: () = {
+    const fn thread_safe<T: Send + Sync>() {}
+    thread_safe::<PtyReader>();
+    thread_safe::<PtyWriter>();
+    thread_safe::<PtyControl>();
+};
+
+impl Terminal {
+    /// Hand out typed halves of the master. `stdin` and `stdout` are moved into them if they're
+    /// still here, otherwise fresh handles are opened.
+    pub fn split(&mut self) -> io::Result<(PtyReader, PtyWriter, PtyControl)> {
+        let reader = match self.stdout.take() {
+            Some(fd) => fd,
+            None => IoFd(self.owner.as_fd().try_clone_to_owned()?),
+        };
+
+        let writer = match self.stdin.take() {
+            Some(fd) => fd,
+            None => IoFd(self.owner.as_fd().try_clone_to_owned()?),
+        };
+
+        let control = PtyControl {
+            master: Arc::new(self.owner.as_fd().try_clone_to_owned()?),
+            pid: self.live.clone(),
+        };
+
+        Ok((PtyReader(reader), PtyWriter(writer), control))
+    }
+}
+
+impl PtyReader {
+    pub fn read_timeout(&mut self, buf: &mut [u8], timeout: Duration) -> io::Result<usize> {
+        self.0.read_timeout(buf, timeout)
+    }
+
+    pub fn into_inner(self) -> IoFd {
+        self.0
+    }
+}
+
+impl PtyWriter {
+    pub fn into_inner(self) -> IoFd {
+        self.0
+    }
+}
+
+impl PtyControl {
+    /// The process currently on the terminal, or `None` once the `Terminal` has reaped it
+    pub fn pid(&self) -> Option<Pid> {
+        self.pid.get()
+    }
+
+    pub fn resize(&self, size: Winsize) -> io::Result<()> {
+        set_winsize(self.master.as_raw_fd(), &size)
+    }
+
+    pub fn signal(&self, signal: Signal) -> io::Result<()> {
+        Ok(kill(self.pid().ok_or_else(no_child)?, signal)?)
+    }
+
+    pub fn signal_group(&self, signal: Signal) -> io::Result<()> {
+        signal_group(self.pid().ok_or_else(no_child)?, signal)
+    }
+
+    pub fn foreground_group(&self) -> io::Result<Pid> {
+        foreground_group(&*self.master)
+    }
+
+    pub fn signal_foreground(&self, signal: Signal) -> io::Result<()> {
+        Ok(killpg(self.foreground_group()?, signal)?)
+    }
+}
+
+impl Read for PtyReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.0.read(buf)
+    }
+}
+
+impl Read for &PtyReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        (&self.0).read(buf)
+    }
+}
+
+impl Write for PtyWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
+
+impl Write for &PtyWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        (&self.0).write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        (&self.0).flush()
+    }
+}
+
+impl AsFd for PtyReader {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.0.as_fd()
+    }
+}
+
+impl AsRawFd for PtyReader {
+    fn as_raw_fd(&self) -> RawFd {
+        self.0.as_raw_fd()
+    }
+}
+
+impl AsFd for PtyWriter {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.0.as_fd()
+    }
+}
+
+impl AsRawFd for PtyWriter {
+    fn as_raw_fd(&self) -> RawFd {
+        self.0.as_raw_fd()
+    }
+}
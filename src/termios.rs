@@ -0,0 +1,105 @@
+use nix::sys::termios::{cfmakeraw, tcgetattr, tcsetattr};
+pub use nix::sys::termios::{
+    ControlFlags, InputFlags, LocalFlags, OutputFlags, SetArg, SpecialCharacterIndices, Termios,
+};
+use std::io;
+
+use crate::Terminal;
+
+impl Terminal {
+    pub fn termios(&self) -> io::Result<Termios> {
+        Ok(tcgetattr(&self.owner)?)
+    }
+
+    pub fn set_termios(&self, termios: &Termios) -> io::Result<()> {
+        self.set_termios_when(SetArg::TCSANOW, termios)
+    }
+
+    pub fn set_termios_when(&self, when: SetArg, termios: &Termios) -> io::Result<()> {
+        Ok(tcsetattr(&self.owner, when, termios)?)
+    }
+
+    /// Read-modify-write the current attributes
+    pub fn update_termios(&self, f: impl FnOnce(&mut Termios)) -> io::Result<()> {
+        let mut termios = self.termios()?;
+        f(&mut termios);
+        self.set_termios(&termios)
+    }
+
+    pub fn set_raw(&self) -> io::Result<()> {
+        self.update_termios(cfmakeraw)
+    }
+
+    pub fn set_cooked(&self) -> io::Result<()> {
+        self.update_termios(cooked)
+    }
+
+    pub fn set_echo(&self, enabled: bool) -> io::Result<()> {
+        self.update_termios(|t| t.local_flags.set(LocalFlags::ECHO, enabled))
+    }
+
+    pub fn set_canonical(&self, enabled: bool) -> io::Result<()> {
+        self.update_termios(|t| t.local_flags.set(LocalFlags::ICANON, enabled))
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android", target_vendor = "apple"))]
+    pub fn set_utf8(&self, enabled: bool) -> io::Result<()> {
+        self.update_termios(|t| t.input_flags.set(InputFlags::IUTF8, enabled))
+    }
+
+    pub fn set_output_processing(&self, enabled: bool) -> io::Result<()> {
+        self.update_termios(|t| t.output_flags.set(OutputFlags::OPOST, enabled))
+    }
+
+    pub fn set_control_char(&self, index: SpecialCharacterIndices, value: u8) -> io::Result<()> {
+        self.update_termios(|t| t.control_chars[index as usize] = value)
+    }
+
+    pub fn set_interrupt_char(&self, value: u8) -> io::Result<()> {
+        self.set_control_char(SpecialCharacterIndices::VINTR, value)
+    }
+
+    pub fn set_eof_char(&self, value: u8) -> io::Result<()> {
+        self.set_control_char(SpecialCharacterIndices::VEOF, value)
+    }
+}
+
+/// Roughly what `stty sane` produces
+pub fn cooked(termios: &mut Termios) {
+    use SpecialCharacterIndices::*;
+
+    termios.input_flags = InputFlags::BRKINT | InputFlags::ICRNL | InputFlags::IXON;
+    #[cfg(any(target_os = "linux", target_os = "android", target_vendor = "apple"))]
+    termios.input_flags.insert(InputFlags::IUTF8);
+
+    termios.output_flags = OutputFlags::OPOST | OutputFlags::ONLCR;
+    termios.control_flags.remove(ControlFlags::CSIZE);
+    termios.control_flags |= ControlFlags::CREAD | ControlFlags::CS8;
+    termios.local_flags = LocalFlags::ISIG
+        | LocalFlags::ICANON
+        | LocalFlags::IEXTEN
+        | LocalFlags::ECHO
+        | LocalFlags::ECHOE
+        | LocalFlags::ECHOK
+        | LocalFlags::ECHOCTL
+        | LocalFlags::ECHOKE;
+
+    for (index, value) in [
+        (VINTR, 0x03),
+        (VQUIT, 0x1c),
+        (VERASE, 0x7f),
+        (VKILL, 0x15),
+        (VEOF, 0x04),
+        (VSTART, 0x11),
+        (VSTOP, 0x13),
+        (VSUSP, 0x1a),
+        (VMIN, 1),
+        (VTIME, 0),
+    ] {
+        termios.control_chars[index as usize] = value;
+    }
+}
+
+pub fn raw(termios: &mut Termios) {
+    cfmakeraw(termios)
+}
@@ -0,0 +1,51 @@
+#![cfg(feature = "tokio")]
+
+use std::time::Duration;
+use termu::{Pid, Process};
+use tokio::{io::AsyncReadExt, time::sleep};
+
+mod common;
+use common::{adopt, read_line, sh};
+
+#[tokio::test]
+async fn reads_to_eof_then_exits() {
+    let mut terminal = sh("echo hello");
+    let mut stdout = terminal.stdout.take().unwrap().into_async().unwrap();
+
+    let mut output = String::new();
+    stdout.read_to_string(&mut output).await.unwrap();
+    assert_eq!(output, "hello\r\n");
+
+    tokio::select! {
+        reason = terminal.exited() => assert!(reason.unwrap().success()),
+        _ = sleep(Duration::from_secs(5)) => panic!("child never exited"),
+    }
+}
+
+#[tokio::test]
+async fn exited_waits_for_running_child() {
+    let mut terminal = sh("sleep 0.1; exit 3");
+
+    tokio::select! {
+        reason = terminal.exited() => assert_eq!(reason.unwrap().code(), Some(3)),
+        _ = sleep(Duration::from_secs(5)) => panic!("child never exited"),
+    }
+}
+
+#[tokio::test]
+async fn exited_does_not_block_on_foreign_zombie() {
+    // The backgrounded sleep is left a zombie, since its parent never reaps it
+    let mut parent = sh("sleep 0.05 & echo $!; exec sleep 5");
+    let mut stdout = parent.stdout.take().unwrap();
+
+    let pid = read_line(&mut stdout).parse().unwrap();
+    let (_pair, mut terminal) = adopt(Some(Process::Pid(Pid::from_raw(pid))));
+
+    // On a current-thread runtime the timer only fires if `exited` yields
+    tokio::select! {
+        _ = terminal.exited() => panic!("zombie reported as gone"),
+        _ = sleep(Duration::from_millis(300)) => {}
+    }
+
+    parent.kill().unwrap();
+}
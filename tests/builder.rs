@@ -0,0 +1,89 @@
+use std::{env, process::Command};
+use termu::{termios, Terminal};
+
+mod common;
+use common::read_all;
+
+/// `sh -c script`, before it reaches the builder
+fn script(source: &str) -> Command {
+    let mut command = Command::new("sh");
+    command.args(["-c", source]);
+    command
+}
+
+#[test]
+fn winsize_is_set_before_exec() {
+    let mut terminal = Terminal::builder(script("stty size"))
+        .size(7, 33)
+        .build()
+        .unwrap();
+
+    assert_eq!(read_all(&mut terminal), "7 33\r\n");
+    assert!(terminal.wait().unwrap().success());
+}
+
+#[test]
+fn term_defaults() {
+    let echo = "echo \"${TERM-unset} ${COLORTERM-unset}\"";
+
+    let mut terminal = Terminal::builder(script(echo)).build().unwrap();
+    assert_eq!(read_all(&mut terminal), "xterm-256color truecolor\r\n");
+
+    let mut terminal = Terminal::builder(script(echo))
+        .term("vt100")
+        .colorterm("24bit")
+        .build()
+        .unwrap();
+    assert_eq!(read_all(&mut terminal), "vt100 24bit\r\n");
+
+    let mut terminal = Terminal::builder(script(echo))
+        .env_remove("TERM")
+        .build()
+        .unwrap();
+    assert_eq!(read_all(&mut terminal), "unset truecolor\r\n");
+
+    // Set on the command before it reached the builder
+    let mut command = script(echo);
+    command.env("TERM", "dumb");
+    let mut terminal = Terminal::builder(command).build().unwrap();
+    assert_eq!(read_all(&mut terminal), "dumb truecolor\r\n");
+}
+
+#[test]
+fn cwd() {
+    let dir = env::temp_dir().canonicalize().unwrap();
+
+    let mut terminal = Terminal::builder(Command::new("pwd"))
+        .cwd(&dir)
+        .build()
+        .unwrap();
+    assert_eq!(read_all(&mut terminal).trim_end(), dir.to_str().unwrap());
+}
+
+#[test]
+fn env() {
+    let mut terminal = Terminal::builder(script("echo \"$A $B $C\""))
+        .env("A", "1")
+        .envs([("B", "2"), ("C", "3")])
+        .build()
+        .unwrap();
+    assert_eq!(read_all(&mut terminal), "1 2 3\r\n");
+}
+
+#[test]
+fn initial_termios() {
+    let mut raw = Terminal::open(&mut Command::new("true"))
+        .unwrap()
+        .termios()
+        .unwrap();
+    termios::raw(&mut raw);
+
+    // Without output processing there's no `\r` added to the newline
+    let mut terminal = Terminal::builder(script(
+        "stty -a | tr ' ' '\\n' | grep -x -e -echo -e -icanon",
+    ))
+    .termios(raw)
+    .build()
+    .unwrap();
+    assert_eq!(read_all(&mut terminal), "-icanon\n-echo\n");
+}
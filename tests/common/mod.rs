@@ -0,0 +1,45 @@
+// Each test binary only uses some of these
+#![allow(dead_code)]
+
+use std::{
+    io::Read,
+    os::fd::{AsFd, OwnedFd},
+    process::Command,
+};
+use termu::{IoFd, Process, PtyPair, Terminal};
+
+pub fn sh(script: &str) -> Terminal {
+    let mut command = Command::new("sh");
+    command.args(["-c", script]);
+    Terminal::open(&mut command).unwrap()
+}
+
+/// A fresh master with `process` adopted onto it. The pair keeps the slave open.
+pub fn adopt(process: Option<Process>) -> (PtyPair, Terminal) {
+    let pair = PtyPair::open().unwrap();
+    let master: OwnedFd = pair.master().as_fd().try_clone_to_owned().unwrap();
+    (pair, Terminal::from_master(master, process).unwrap())
+}
+
+/// Read up to the next newline, one byte at a time so nothing after it is consumed
+pub fn read_line(fd: &mut IoFd) -> String {
+    let mut line = Vec::new();
+    let mut byte = [0];
+    while fd.read(&mut byte).unwrap() == 1 && byte[0] != b'\n' {
+        line.push(byte[0]);
+    }
+
+    String::from_utf8(line).unwrap().trim_end().to_owned()
+}
+
+/// Everything the child writes until it hangs up
+pub fn read_all(terminal: &mut Terminal) -> String {
+    let mut output = String::new();
+    terminal
+        .stdout
+        .as_mut()
+        .unwrap()
+        .read_to_string(&mut output)
+        .unwrap();
+    output
+}
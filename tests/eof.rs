@@ -0,0 +1,70 @@
+use std::io::Read;
+
+mod common;
+use common::sh;
+
+#[test]
+fn drains_short_lived_child() {
+    let mut terminal = sh("echo hello");
+    let mut output = String::new();
+
+    terminal
+        .stdout
+        .take()
+        .unwrap()
+        .read_to_string(&mut output)
+        .unwrap();
+
+    assert_eq!(output, "hello\r\n");
+    assert!(terminal.is_hung_up().unwrap());
+    assert!(terminal.wait().unwrap().success());
+}
+
+#[test]
+fn drains_large_output_to_completion() {
+    let mut terminal = sh("seq 1 20000");
+    let mut output = String::new();
+
+    terminal
+        .stdout
+        .take()
+        .unwrap()
+        .read_to_string(&mut output)
+        .unwrap();
+
+    let lines = output.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 20000);
+    assert_eq!(lines.last(), Some(&"20000"));
+}
+
+#[test]
+fn keeps_reporting_eof() {
+    let mut terminal = sh("printf done");
+    let mut stdout = terminal.stdout.take().unwrap();
+
+    let mut output = Vec::new();
+    stdout.read_to_end(&mut output).unwrap();
+    assert_eq!(output, b"done");
+
+    let mut buf = [0; 16];
+    assert_eq!(stdout.read(&mut buf).unwrap(), 0);
+    assert_eq!(stdout.read(&mut buf).unwrap(), 0);
+}
+
+#[test]
+fn not_hung_up_while_child_runs() {
+    let mut terminal = sh("sleep 10");
+    assert!(!terminal.is_hung_up().unwrap());
+
+    terminal.kill().unwrap();
+
+    let mut output = Vec::new();
+    terminal
+        .stdout
+        .take()
+        .unwrap()
+        .read_to_end(&mut output)
+        .unwrap();
+
+    assert!(terminal.is_hung_up().unwrap());
+}
@@ -0,0 +1,57 @@
+use nix::errno::Errno;
+use std::{error::Error as _, io, process::Command};
+use termu::{Error, Terminal};
+
+#[test]
+fn missing_binary_fails_to_exec() {
+    let Err(error) = Terminal::open(&mut Command::new("/nonexistent/termu-test")) else {
+        panic!("exec should have failed");
+    };
+    assert_eq!(error, Error::Exec(Errno::ENOENT));
+    assert_eq!(error.errno(), Errno::ENOENT);
+}
+
+#[test]
+fn display_names_the_stage() {
+    assert_eq!(
+        Error::Exec(Errno::ENOENT).to_string(),
+        format!("failed to spawn child: {}", Errno::ENOENT)
+    );
+    assert_eq!(
+        Error::ControllingTty(Errno::EPERM).to_string(),
+        format!(
+            "failed to set controlling terminal in child: {}",
+            Errno::EPERM
+        )
+    );
+    assert_eq!(
+        Error::Busy.to_string(),
+        format!(
+            "failed to attach while the previous process is running: {}",
+            Errno::EBUSY
+        )
+    );
+}
+
+#[test]
+fn source_is_the_errno() {
+    let error = Error::Grant(Errno::EACCES);
+    let source = error.source().unwrap().downcast_ref::<Errno>();
+    assert_eq!(source, Some(&Errno::EACCES));
+
+    assert!(Error::Busy.source().is_none());
+}
+
+#[test]
+fn into_io_error() {
+    let error = io::Error::from(Error::Exec(Errno::ENOENT));
+    assert_eq!(error.kind(), io::ErrorKind::NotFound);
+    assert_eq!(error.to_string(), Error::Exec(Errno::ENOENT).to_string());
+
+    // The original error is still there to get back at
+    let inner = error.into_inner().unwrap().downcast::<Error>().unwrap();
+    assert_eq!(*inner, Error::Exec(Errno::ENOENT));
+
+    let error = io::Error::from(Error::Busy);
+    assert_eq!(error.kind(), io::ErrorKind::ResourceBusy);
+}
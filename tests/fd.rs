@@ -0,0 +1,55 @@
+use std::io::{ErrorKind, Read, Write};
+
+mod common;
+use common::{read_line, sh};
+
+#[test]
+fn nonblocking_read_would_block() {
+    let mut terminal = sh("read line; echo \"got $line\"");
+    let stdout = terminal.stdout.as_mut().unwrap();
+    stdout.set_nonblocking(true).unwrap();
+
+    let mut buf = [0; 16];
+    let error = stdout.read(&mut buf).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::WouldBlock);
+
+    // Shared with every other handle on the master, so the write side goes back to blocking too
+    stdout.set_nonblocking(false).unwrap();
+    terminal.stdin.as_mut().unwrap().write_all(b"hi\n").unwrap();
+
+    let stdout = terminal.stdout.as_mut().unwrap();
+    assert_eq!(read_line(stdout), "hi");
+    assert_eq!(read_line(stdout), "got hi");
+    assert!(terminal.wait().unwrap().success());
+}
+
+#[cfg(feature = "mio")]
+#[test]
+fn mio_registration_fires_on_output() {
+    use mio::{Events, Interest, Poll, Token};
+    use std::time::Duration;
+
+    let mut terminal = sh("read line; echo \"got $line\"");
+    let mut stdout = terminal.stdout.take().unwrap();
+
+    let mut poll = Poll::new().unwrap();
+    poll.registry()
+        .register(&mut stdout, Token(1), Interest::READABLE)
+        .unwrap();
+
+    // Nothing to read until the child is told to say something
+    let mut events = Events::with_capacity(4);
+    poll.poll(&mut events, Some(Duration::from_millis(50)))
+        .unwrap();
+    assert!(events.is_empty());
+
+    terminal.stdin.as_mut().unwrap().write_all(b"hi\n").unwrap();
+    poll.poll(&mut events, Some(Duration::from_secs(5)))
+        .unwrap();
+    let event = events.iter().next().unwrap();
+    assert_eq!(event.token(), Token(1));
+    assert!(event.is_readable());
+
+    poll.registry().deregister(&mut stdout).unwrap();
+    assert!(terminal.wait().unwrap().success());
+}
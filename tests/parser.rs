@@ -0,0 +1,316 @@
+use termu::parser::{Params, Parser, Perform, StringKind, MAX_PARAMS};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Event {
+    Print(char),
+    Execute(u8),
+    Hook(Vec<Vec<u16>>, Vec<u8>, bool, char),
+    Put(u8),
+    Unhook,
+    Osc(Vec<Vec<u8>>, bool),
+    Csi(Vec<Vec<u16>>, Vec<u8>, bool, char),
+    Esc(Vec<u8>, bool, u8),
+    String(StringKind, Vec<u8>),
+}
+
+#[derive(Default)]
+struct Recorder(Vec<Event>);
+
+fn groups(params: &Params) -> Vec<Vec<u16>> {
+    params.iter().map(<[u16]>::to_vec).collect()
+}
+
+impl Perform for Recorder {
+    fn print(&mut self, c: char) {
+        self.0.push(Event::Print(c));
+    }
+
+    fn execute(&mut self, byte: u8) {
+        self.0.push(Event::Execute(byte));
+    }
+
+    fn hook(&mut self, params: &Params, intermediates: &[u8], ignore: bool, action: char) {
+        self.0.push(Event::Hook(
+            groups(params),
+            intermediates.to_vec(),
+            ignore,
+            action,
+        ));
+    }
+
+    fn put(&mut self, byte: u8) {
+        self.0.push(Event::Put(byte));
+    }
+
+    fn unhook(&mut self) {
+        self.0.push(Event::Unhook);
+    }
+
+    fn osc_dispatch(&mut self, params: &[&[u8]], bell_terminated: bool) {
+        let params = params.iter().map(|param| param.to_vec()).collect();
+        self.0.push(Event::Osc(params, bell_terminated));
+    }
+
+    fn csi_dispatch(&mut self, params: &Params, intermediates: &[u8], ignore: bool, action: char) {
+        self.0.push(Event::Csi(
+            groups(params),
+            intermediates.to_vec(),
+            ignore,
+            action,
+        ));
+    }
+
+    fn esc_dispatch(&mut self, intermediates: &[u8], ignore: bool, byte: u8) {
+        self.0
+            .push(Event::Esc(intermediates.to_vec(), ignore, byte));
+    }
+
+    fn string_dispatch(&mut self, kind: StringKind, data: &[u8]) {
+        self.0.push(Event::String(kind, data.to_vec()));
+    }
+}
+
+fn parse(input: &[u8]) -> Vec<Event> {
+    let mut recorder = Recorder::default();
+    Parser::new().advance(&mut recorder, input);
+    recorder.0
+}
+
+/// The same input fed in one go and a byte at a time has to come out the same
+fn parse_both(input: &[u8]) -> Vec<Event> {
+    let mut parser = Parser::new();
+    let mut recorder = Recorder::default();
+    for byte in input {
+        parser.advance(&mut recorder, &[*byte]);
+    }
+
+    let events = parse(input);
+    assert_eq!(recorder.0, events);
+    events
+}
+
+fn csi(params: &[&[u16]], intermediates: &[u8], action: char) -> Event {
+    let params = params.iter().map(|group| group.to_vec()).collect();
+    Event::Csi(params, intermediates.to_vec(), false, action)
+}
+
+#[test]
+fn csi_params() {
+    assert_eq!(parse_both(b"\x1b[H"), [csi(&[&[0]], b"", 'H')]);
+    assert_eq!(parse_both(b"\x1b[12;34H"), [csi(&[&[12], &[34]], b"", 'H')]);
+    assert_eq!(parse_both(b"\x1b[;5H"), [csi(&[&[0], &[5]], b"", 'H')]);
+    assert_eq!(parse_both(b"\x1b[99999m"), [csi(&[&[u16::MAX]], b"", 'm')]);
+}
+
+#[test]
+fn csi_subparams() {
+    assert_eq!(
+        parse_both(b"\x1b[4:3;38:2::1:2:3m"),
+        [csi(&[&[4, 3], &[38, 2, 0, 1, 2, 3]], b"", 'm')]
+    );
+    assert_eq!(parse_both(b"\x1b[1;4:m"), [csi(&[&[1], &[4, 0]], b"", 'm')]);
+}
+
+#[test]
+fn csi_private_markers_and_intermediates() {
+    assert_eq!(parse_both(b"\x1b[?1049h"), [csi(&[&[1049]], b"?", 'h')]);
+    assert_eq!(parse_both(b"\x1b[>c"), [csi(&[&[0]], b">", 'c')]);
+    assert_eq!(parse_both(b"\x1b[?2026$p"), [csi(&[&[2026]], b"?$", 'p')]);
+
+    // A marker after the parameters spoils the whole sequence
+    assert_eq!(parse_both(b"\x1b[1?hx"), [Event::Print('x')]);
+}
+
+#[test]
+fn csi_overflow_sets_ignore() {
+    let mut input = b"\x1b[".to_vec();
+    input.extend(b"1;".repeat(MAX_PARAMS));
+    input.extend(b"1m");
+
+    let [Event::Csi(params, _, ignore, 'm')] = &parse_both(&input)[..] else {
+        panic!("expected a single CSI");
+    };
+    assert_eq!(params.len(), MAX_PARAMS);
+    assert!(ignore);
+
+    assert_eq!(
+        parse_both(b"\x1b[?$$p"),
+        [Event::Csi(vec![vec![0]], b"?$".to_vec(), true, 'p')]
+    );
+}
+
+#[test]
+fn controls_inside_csi() {
+    assert_eq!(
+        parse_both(b"\x1b[1\n2H"),
+        [Event::Execute(b'\n'), csi(&[&[12]], b"", 'H')]
+    );
+}
+
+#[test]
+fn esc_dispatch() {
+    assert_eq!(parse_both(b"\x1b7"), [Event::Esc(vec![], false, b'7')]);
+    assert_eq!(
+        parse_both(b"\x1b(0"),
+        [Event::Esc(b"(".to_vec(), false, b'0')]
+    );
+    assert_eq!(
+        parse_both(b"\x1b#%!8"),
+        [Event::Esc(b"#%".to_vec(), true, b'8')]
+    );
+}
+
+#[test]
+fn osc_terminators() {
+    let title = vec![b"0".to_vec(), b"title".to_vec()];
+
+    assert_eq!(
+        parse_both(b"\x1b]0;title\x07"),
+        [Event::Osc(title.clone(), true)]
+    );
+    assert_eq!(
+        parse_both(b"\x1b]0;title\x1b\\"),
+        [Event::Osc(title, false), Event::Esc(vec![], false, b'\\')]
+    );
+}
+
+#[test]
+fn osc_params() {
+    assert_eq!(
+        parse_both(b"\x1b]8;;https://a;b\x07"),
+        [Event::Osc(
+            vec![
+                b"8".to_vec(),
+                b"".to_vec(),
+                b"https://a".to_vec(),
+                b"b".to_vec()
+            ],
+            true
+        )]
+    );
+    assert_eq!(parse_both(b"\x1b]\x07"), [Event::Osc(vec![vec![]], true)]);
+}
+
+#[test]
+fn dcs_hook_put_unhook() {
+    assert_eq!(
+        parse_both(b"\x1bP1$qm\x1b\\"),
+        [
+            Event::Hook(vec![vec![1]], b"$".to_vec(), false, 'q'),
+            Event::Put(b'm'),
+            Event::Unhook,
+            Event::Esc(vec![], false, b'\\'),
+        ]
+    );
+    assert_eq!(
+        parse_both(b"\x1bP>|a\x7fb\x1b\\"),
+        [
+            Event::Hook(vec![vec![0]], b">".to_vec(), false, '|'),
+            Event::Put(b'a'),
+            Event::Put(b'b'),
+            Event::Unhook,
+            Event::Esc(vec![], false, b'\\'),
+        ]
+    );
+}
+
+#[test]
+fn sos_pm_apc() {
+    for (introducer, kind) in [
+        (b'X', StringKind::Sos),
+        (b'^', StringKind::Pm),
+        (b'_', StringKind::Apc),
+    ] {
+        let mut input = vec![0x1b, introducer];
+        input.extend_from_slice(b"a;\x07b\x1b\\");
+
+        assert_eq!(
+            parse_both(&input),
+            [
+                Event::String(kind, b"a;\x07b".to_vec()),
+                Event::Esc(vec![], false, b'\\'),
+            ]
+        );
+    }
+}
+
+#[test]
+fn can_and_sub_abort() {
+    for abort in [0x18, 0x1a] {
+        let mut input = b"\x1b[12".to_vec();
+        input.extend([abort, b'H']);
+        assert_eq!(
+            parse_both(&input),
+            [Event::Execute(abort), Event::Print('H')]
+        );
+
+        // Strings are dropped rather than dispatched
+        for string in [&b"\x1b]0;title"[..], b"\x1b_data"] {
+            let mut input = string.to_vec();
+            input.extend([abort, b'x']);
+            assert_eq!(
+                parse_both(&input),
+                [Event::Execute(abort), Event::Print('x')]
+            );
+        }
+
+        let mut input = b"\x1bPqab".to_vec();
+        input.extend([abort, b'x']);
+        assert_eq!(
+            parse_both(&input),
+            [
+                Event::Hook(vec![vec![0]], vec![], false, 'q'),
+                Event::Put(b'a'),
+                Event::Put(b'b'),
+                Event::Unhook,
+                Event::Execute(abort),
+                Event::Print('x'),
+            ]
+        );
+    }
+}
+
+#[test]
+fn esc_restarts_sequence() {
+    assert_eq!(parse_both(b"\x1b[12\x1b[3A"), [csi(&[&[3]], b"", 'A')]);
+}
+
+#[test]
+fn utf8() {
+    assert_eq!(
+        parse_both("aé漢😀".as_bytes()),
+        [
+            Event::Print('a'),
+            Event::Print('é'),
+            Event::Print('漢'),
+            Event::Print('😀'),
+        ]
+    );
+}
+
+#[test]
+fn utf8_split_across_calls() {
+    let input = "漢😀".as_bytes();
+    let mut parser = Parser::new();
+    let mut recorder = Recorder::default();
+
+    parser.advance(&mut recorder, &input[..2]);
+    assert_eq!(recorder.0, []);
+    parser.advance(&mut recorder, &input[2..5]);
+    assert_eq!(recorder.0, [Event::Print('漢')]);
+    parser.advance(&mut recorder, &input[5..]);
+    assert_eq!(recorder.0, [Event::Print('漢'), Event::Print('😀')]);
+}
+
+#[test]
+fn invalid_utf8() {
+    assert_eq!(
+        parse_both(b"\xe6\xbca\xffb"),
+        [
+            Event::Print(char::REPLACEMENT_CHARACTER),
+            Event::Print('a'),
+            Event::Print(char::REPLACEMENT_CHARACTER),
+            Event::Print('b'),
+        ]
+    );
+}
@@ -0,0 +1,161 @@
+use std::{
+    fs::File,
+    io::{ErrorKind, Read},
+    os::fd::OwnedFd,
+    process::Command,
+    time::{Duration, Instant},
+};
+use termu::{DropPolicy, Error, ExitReason, Pid, Process, PtyPair, Readiness, Signal, Terminal};
+
+mod common;
+use common::{adopt, read_all, read_line, sh};
+
+#[test]
+fn no_foreground_group_once_session_ends() {
+    let mut terminal = sh("exit 0");
+
+    let mut output = Vec::new();
+    terminal
+        .stdout
+        .take()
+        .unwrap()
+        .read_to_end(&mut output)
+        .unwrap();
+    assert!(terminal.wait().unwrap().success());
+
+    // A group of 0 would have killpg signal the test process itself
+    let error = terminal.foreground_group().unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::NotFound);
+    assert!(terminal.signal_foreground(Signal::SIGTERM).is_err());
+}
+
+#[test]
+fn no_signalling_once_reaped() {
+    let mut terminal = sh("exit 3");
+    assert_eq!(terminal.wait().unwrap(), ExitReason::Exited(3));
+
+    // The pid is still known, but may already belong to someone else
+    assert!(terminal.pid().is_some());
+    for error in [
+        terminal.signal(Signal::SIGTERM).unwrap_err(),
+        terminal.signal_group(Signal::SIGTERM).unwrap_err(),
+    ] {
+        assert_eq!(error.kind(), ErrorKind::NotFound);
+    }
+
+    assert_eq!(terminal.kill().unwrap(), ExitReason::Exited(3));
+}
+
+#[test]
+fn from_master_rejects_other_fds() {
+    let pair = PtyPair::open().unwrap();
+    let slave = pair.slave().try_clone_to_owned().unwrap();
+    assert!(matches!(
+        Terminal::from_master(slave, None),
+        Err(Error::NotMaster(_))
+    ));
+
+    let null = OwnedFd::from(File::open("/dev/null").unwrap());
+    assert!(matches!(
+        Terminal::from_master(null, None),
+        Err(Error::NotMaster(_))
+    ));
+}
+
+#[test]
+fn waits_on_adopted_pid() {
+    // Reaped through the terminal rather than the `Child`
+    let mut command = Command::new("sh");
+    command.args(["-c", "sleep 0.1; exit 2"]);
+    let pid = Pid::from_raw(command.spawn().unwrap().id() as i32);
+
+    let (_pair, mut terminal) = adopt(Some(Process::Pid(pid)));
+    assert_eq!(terminal.pid(), Some(pid));
+    assert_eq!(terminal.try_wait().unwrap(), None);
+
+    assert_eq!(terminal.wait().unwrap(), ExitReason::Exited(2));
+    assert_eq!(terminal.try_wait().unwrap(), Some(ExitReason::Exited(2)));
+}
+
+#[test]
+fn adopted_process_outlives_terminal() {
+    let mut child = Command::new("sleep").arg("10").spawn().unwrap();
+    let pid = Pid::from_raw(child.id() as i32);
+
+    let (_pair, terminal) = adopt(Some(Process::Pid(pid)));
+    assert_eq!(terminal.drop_policy(), DropPolicy::Detach);
+    drop(terminal);
+
+    assert!(child.try_wait().unwrap().is_none());
+    child.kill().unwrap();
+    child.wait().unwrap();
+}
+
+#[test]
+fn killing_adopted_process_spares_our_group() {
+    // Spawned without a group of its own, so it shares the test's
+    let mut command = Command::new("sleep");
+    command.arg("10");
+
+    let (_pair, mut terminal) = adopt(Some(Process::Child(command.spawn().unwrap())));
+    terminal.signal_group(Signal::SIGTERM).unwrap();
+    assert_eq!(terminal.wait().unwrap().signal(), Some(Signal::SIGTERM));
+
+    let (_pair, mut terminal) = adopt(Some(Process::Child(command.spawn().unwrap())));
+    terminal.set_drop_policy(DropPolicy::Kill);
+    drop(terminal);
+}
+
+#[test]
+fn shutdown_hangs_up_and_leaves_output_to_drain() {
+    let mut terminal = sh("echo bye; sleep 10");
+    terminal
+        .poll(Readiness::READABLE, Some(Duration::from_secs(5)))
+        .unwrap();
+
+    let reason = terminal.shutdown(Duration::from_secs(5)).unwrap();
+    assert_eq!(reason.and_then(|r| r.signal()), Some(Signal::SIGHUP));
+    assert_eq!(read_all(&mut terminal), "bye\r\n");
+
+    // Nothing left to stop
+    assert_eq!(
+        terminal.shutdown(Duration::ZERO).unwrap(),
+        Some(reason.unwrap())
+    );
+}
+
+#[test]
+fn shutdown_escalates_to_sigkill() {
+    let mut terminal = sh("trap '' HUP; echo ready; while :; do sleep 1; done");
+    assert_eq!(read_line(terminal.stdout.as_mut().unwrap()), "ready");
+
+    let started = Instant::now();
+    let reason = terminal.shutdown(Duration::from_millis(100)).unwrap();
+    assert_eq!(reason.and_then(|r| r.signal()), Some(Signal::SIGKILL));
+    assert!(started.elapsed() < Duration::from_secs(5));
+}
+
+#[test]
+fn drop_policies() {
+    for policy in [DropPolicy::Kill, DropPolicy::default()] {
+        let mut terminal = sh("sleep 10");
+        let pid = terminal.pid().unwrap();
+        terminal.set_drop_policy(policy);
+        drop(terminal);
+
+        // Already reaped, so it's no longer our child to wait on
+        let (_pair, mut adopted) = adopt(Some(Process::Pid(pid)));
+        assert_eq!(adopted.try_wait().unwrap(), Some(ExitReason::Unknown));
+    }
+
+    // Closing the master still hangs the child up
+    let mut terminal = sh("trap '' HUP; echo ready; sleep 10");
+    assert_eq!(read_line(terminal.stdout.as_mut().unwrap()), "ready");
+    let pid = terminal.pid().unwrap();
+    terminal.set_drop_policy(DropPolicy::Detach);
+    drop(terminal);
+
+    let (_pair, mut adopted) = adopt(Some(Process::Pid(pid)));
+    assert_eq!(adopted.try_wait().unwrap(), None);
+    assert_eq!(adopted.kill().unwrap().signal(), Some(Signal::SIGKILL));
+}
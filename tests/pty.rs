@@ -0,0 +1,66 @@
+use std::{os::fd::AsRawFd, process::Command};
+use termu::{Error, Pid, PtyPair, Terminal};
+
+mod common;
+use common::read_all;
+
+#[test]
+fn opens_pair() {
+    let pair = PtyPair::open().unwrap();
+
+    assert!(pair.slave_path().starts_with("/dev/pts"));
+    assert!(pair.slave_path().exists());
+    assert_ne!(pair.master().as_raw_fd(), pair.slave().as_raw_fd());
+}
+
+#[test]
+fn terminal_keeps_pair_slave_path() {
+    let pair = PtyPair::open().unwrap();
+    let path = pair.slave_path().to_owned();
+
+    let mut terminal = Terminal::from_pair(pair, &mut Command::new("tty")).unwrap();
+    assert_eq!(terminal.slave_path(), path);
+
+    let output = read_all(&mut terminal);
+    assert_eq!(output.trim_end(), path.to_str().unwrap());
+    assert!(terminal.wait().unwrap().success());
+}
+
+#[test]
+fn attaches_in_sequence() {
+    let mut command = Command::new("sh");
+    command.args(["-c", "echo $$"]);
+
+    let mut terminal = Terminal::open(&mut command).unwrap();
+    let mut pids = vec![terminal.pid().unwrap()];
+    assert!(terminal.wait().unwrap().success());
+
+    // The same command again, which already went through one spawn
+    for _ in 0..2 {
+        terminal.attach(&mut command).unwrap();
+        pids.push(terminal.pid().unwrap());
+        assert!(terminal.wait().unwrap().success());
+    }
+
+    pids.dedup();
+    assert_eq!(pids.len(), 3);
+
+    let output = read_all(&mut terminal);
+    let echoed = output
+        .lines()
+        .map(|line| Pid::from_raw(line.parse().unwrap()))
+        .collect::<Vec<_>>();
+    assert_eq!(echoed, pids);
+}
+
+#[test]
+fn attach_refuses_running_child() {
+    let mut terminal = Terminal::open(&mut Command::new("cat")).unwrap();
+
+    let error = terminal.attach(&mut Command::new("true")).unwrap_err();
+    assert_eq!(error, Error::Busy);
+
+    terminal.kill().unwrap();
+    terminal.attach(&mut Command::new("true")).unwrap();
+    assert!(terminal.wait().unwrap().success());
+}
@@ -0,0 +1,89 @@
+use std::{
+    io::{ErrorKind, Write},
+    time::{Duration, Instant},
+};
+use termu::{ExitReason, Pid, Process, Readiness};
+
+mod common;
+use common::{adopt, read_line, sh};
+
+#[test]
+fn read_timeout_times_out() {
+    let mut terminal = sh("sleep 10");
+    let mut stdout = terminal.stdout.take().unwrap();
+
+    let mut buf = [0; 16];
+    let error = stdout
+        .read_timeout(&mut buf, Duration::from_millis(50))
+        .unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::TimedOut);
+
+    terminal.kill().unwrap();
+}
+
+#[test]
+fn readable_after_output() {
+    let mut terminal = sh("echo hi; sleep 10");
+
+    let ready = terminal
+        .poll(Readiness::READABLE, Some(Duration::from_secs(5)))
+        .unwrap();
+    assert_eq!(ready, Readiness::READABLE);
+
+    let mut buf = [0; 16];
+    let n = terminal
+        .stdout
+        .as_mut()
+        .unwrap()
+        .read_timeout(&mut buf, Duration::from_secs(5))
+        .unwrap();
+    assert!(buf[..n].starts_with(b"hi"));
+
+    terminal.kill().unwrap();
+}
+
+#[test]
+fn exited_through_pidfd() {
+    let mut terminal = sh("sleep 0.05; exit 4");
+
+    let ready = terminal.poll(Readiness::EXITED, None).unwrap();
+    assert_eq!(ready, Readiness::EXITED);
+    assert_eq!(terminal.try_wait().unwrap(), Some(ExitReason::Exited(4)));
+}
+
+#[test]
+fn exited_once_foreign_zombie_is_reaped() {
+    // The backgrounded sleep stays a zombie, with its pidfd readable, until the shell gets to
+    // `wait`. From then on the exit can only be noticed by checking.
+    let mut parent = sh("sleep 0.05 & echo $!; read line; wait");
+    let mut stdout = parent.stdout.take().unwrap();
+
+    let pid = read_line(&mut stdout).parse().unwrap();
+
+    let (_pair, mut terminal) = adopt(Some(Process::Pid(Pid::from_raw(pid))));
+
+    std::thread::scope(|scope| {
+        scope.spawn(|| {
+            std::thread::sleep(Duration::from_millis(200));
+            parent.stdin.as_mut().unwrap().write_all(b"\n").unwrap();
+        });
+
+        let ready = terminal
+            .poll(Readiness::EXITED, Some(Duration::from_secs(5)))
+            .unwrap();
+        assert_eq!(ready, Readiness::EXITED);
+    });
+
+    assert_eq!(terminal.try_wait().unwrap(), Some(ExitReason::Unknown));
+    assert!(parent.wait().unwrap().success());
+}
+
+#[test]
+fn nothing_to_wait_on() {
+    let (_pair, mut terminal) = adopt(None);
+
+    let started = Instant::now();
+    assert!(terminal.poll(Readiness::empty(), None).unwrap().is_empty());
+    assert!(terminal.poll(Readiness::EXITED, None).unwrap().is_empty());
+    assert!(started.elapsed() < Duration::from_secs(1));
+}
@@ -0,0 +1,798 @@
+use std::io::Write;
+use termu::screen::{Attrs, Buffer, Color, Mode, Screen, Style, Underline, UnicodeVersion};
+
+fn screen(rows: usize, cols: usize, input: &str) -> Screen {
+    let mut screen = Screen::new(rows, cols);
+    screen.process(input.as_bytes());
+    screen
+}
+
+fn cursor(screen: &Screen) -> (usize, usize) {
+    (screen.cursor().row, screen.cursor().col)
+}
+
+/// Five rows of `0123456789`
+const FILLED: &str = "0123456789\r\n0123456789\r\n0123456789\r\n0123456789\r\n0123456789";
+
+#[test]
+fn prints_into_cells() {
+    let screen = screen(3, 10, "hello");
+
+    assert_eq!(screen.contents(), "hello");
+    assert_eq!(cursor(&screen), (0, 5));
+    assert_eq!(screen.cell(0, 1).unwrap().grapheme(), "e");
+    assert!(screen.cell(0, 5).unwrap().is_blank());
+    assert_eq!(screen.cell(3, 0), None);
+    assert_eq!(screen.cell(0, 10), None);
+}
+
+#[test]
+fn contents_trims_trailing_blanks() {
+    let screen = screen(5, 10, "a  \r\n\r\n  b   ");
+    assert_eq!(screen.contents(), "a\n\n  b");
+
+    assert_eq!(Screen::new(5, 10).contents(), "");
+}
+
+#[test]
+fn controls_move_the_cursor() {
+    let mut screen = screen(3, 10, "abc\rx\n");
+    assert_eq!(screen.contents(), "xbc");
+    assert_eq!(cursor(&screen), (1, 1));
+
+    screen.process(b"de\x08f");
+    assert_eq!(screen.contents(), "xbc\n df");
+}
+
+#[test]
+fn pending_wrap_is_cancelled_by_moving() {
+    let mut screen = screen(2, 5, "abcde");
+    assert!(screen.cursor().pending_wrap);
+
+    screen.process(b"\r");
+    assert!(!screen.cursor().pending_wrap);
+    screen.process(b"x");
+    assert_eq!(screen.contents(), "xbcde");
+
+    screen.process(b"\x1b[1;5Hy");
+    assert!(screen.cursor().pending_wrap);
+    screen.process(b"\x1b[Dz");
+    assert_eq!(screen.contents(), "xbczy");
+    assert!(!screen.row(0).unwrap().is_wrapped());
+}
+
+#[test]
+fn feeds_through_io_write() {
+    let mut screen = Screen::new(3, 10);
+    let bytes = "\x1b[1m漢字\x1b[0m!".as_bytes();
+
+    // Split inside both the escape sequence and a character
+    for chunk in bytes.chunks(3) {
+        screen.write_all(chunk).unwrap();
+    }
+    write!(screen, "\r\n{}", 42).unwrap();
+    screen.flush().unwrap();
+
+    assert_eq!(screen.contents(), "漢字!\n42");
+    assert!(screen.cell(0, 0).unwrap().style.attrs.contains(Attrs::BOLD));
+    assert_eq!(screen.cell(0, 4).unwrap().style, Style::default());
+}
+
+/// The style left after `input`
+fn sgr(input: &str) -> Style {
+    screen(1, 1, input).cursor().style
+}
+
+#[test]
+fn sgr_attributes_set_and_clear() {
+    let all = Attrs::BOLD
+        | Attrs::DIM
+        | Attrs::ITALIC
+        | Attrs::BLINK
+        | Attrs::INVERSE
+        | Attrs::HIDDEN
+        | Attrs::STRIKETHROUGH
+        | Attrs::OVERLINE;
+    assert_eq!(sgr("\x1b[1;2;3;5;7;8;9;53m").attrs, all);
+    assert_eq!(sgr("\x1b[6m").attrs, Attrs::BLINK);
+
+    let cleared = sgr("\x1b[1;2;3;5;7;8;9;53m\x1b[22;23;25;27;28;29;55m");
+    assert_eq!(cleared.attrs, Attrs::empty());
+}
+
+#[test]
+fn sgr_underline_styles() {
+    assert_eq!(sgr("\x1b[4m").underline, Underline::Single);
+    assert_eq!(sgr("\x1b[4m\x1b[4:0m").underline, Underline::None);
+    assert_eq!(sgr("\x1b[4:1m").underline, Underline::Single);
+    assert_eq!(sgr("\x1b[4:2m").underline, Underline::Double);
+    assert_eq!(sgr("\x1b[4:3m").underline, Underline::Curly);
+    assert_eq!(sgr("\x1b[4:4m").underline, Underline::Dotted);
+    assert_eq!(sgr("\x1b[4:5m").underline, Underline::Dashed);
+    assert_eq!(sgr("\x1b[21m").underline, Underline::Double);
+    assert_eq!(sgr("\x1b[4:3m\x1b[24m").underline, Underline::None);
+}
+
+#[test]
+fn sgr_basic_colours() {
+    let style = sgr("\x1b[31;42m");
+    assert_eq!((style.fg, style.bg), (Color::Indexed(1), Color::Indexed(2)));
+
+    let style = sgr("\x1b[97;100m");
+    assert_eq!(
+        (style.fg, style.bg),
+        (Color::Indexed(15), Color::Indexed(8))
+    );
+
+    let style = sgr("\x1b[31;42m\x1b[39;49m");
+    assert_eq!((style.fg, style.bg), (Color::Default, Color::Default));
+}
+
+#[test]
+fn sgr_indexed_colours_in_both_forms() {
+    for input in ["\x1b[38;5;200m", "\x1b[38:5:200m"] {
+        assert_eq!(sgr(input).fg, Color::Indexed(200), "{input:?}");
+    }
+    for input in ["\x1b[48;5;17m", "\x1b[48:5:17m"] {
+        assert_eq!(sgr(input).bg, Color::Indexed(17), "{input:?}");
+    }
+
+    // The `;` form takes the parameters after it, and the rest still apply
+    let style = sgr("\x1b[38;5;1;1m");
+    assert_eq!(style.fg, Color::Indexed(1));
+    assert_eq!(style.attrs, Attrs::BOLD);
+}
+
+#[test]
+fn sgr_rgb_colours_in_both_forms() {
+    for input in [
+        "\x1b[38;2;10;20;30m",
+        "\x1b[38:2::10:20:30m",
+        "\x1b[38:2:10:20:30m",
+    ] {
+        assert_eq!(sgr(input).fg, Color::Rgb(10, 20, 30), "{input:?}");
+    }
+
+    let style = sgr("\x1b[48;2;1;2;3;4m");
+    assert_eq!(style.bg, Color::Rgb(1, 2, 3));
+    assert_eq!(style.underline, Underline::Single);
+}
+
+#[test]
+fn sgr_underline_colour() {
+    assert_eq!(sgr("\x1b[58;5;9m").underline_color, Color::Indexed(9));
+    assert_eq!(
+        sgr("\x1b[58:2::1:2:3m").underline_color,
+        Color::Rgb(1, 2, 3)
+    );
+    assert_eq!(sgr("\x1b[58:5:9m\x1b[59m").underline_color, Color::Default);
+
+    // Separate from the foreground
+    assert_eq!(sgr("\x1b[58:5:9m").fg, Color::Default);
+}
+
+#[test]
+fn sgr_out_of_range_colours_are_ignored() {
+    assert_eq!(sgr("\x1b[38;5;256m").fg, Color::Default);
+    assert_eq!(sgr("\x1b[38:5:256m").fg, Color::Default);
+    assert_eq!(sgr("\x1b[31m\x1b[38:2::300:0:0m").fg, Color::Indexed(1));
+    assert_eq!(sgr("\x1b[48;2;0;0;256m").bg, Color::Default);
+    assert_eq!(sgr("\x1b[38;9;1m").fg, Color::Default);
+
+    // Everything after a bad colour is still read
+    assert_eq!(sgr("\x1b[38;5;999;1m").attrs, Attrs::BOLD);
+}
+
+#[test]
+fn sgr_reset() {
+    let set = "\x1b[1;4:3;31;48;5;7;58;5;1m";
+    assert_ne!(sgr(set), Style::default());
+
+    assert_eq!(sgr(&format!("{set}\x1b[m")), Style::default());
+    assert_eq!(sgr(&format!("{set}\x1b[0m")), Style::default());
+
+    let style = sgr(&format!("{set}\x1b[0;3m"));
+    assert_eq!(style.attrs, Attrs::ITALIC);
+    assert_eq!(style.fg, Color::Default);
+}
+
+#[test]
+fn relative_movement_is_clamped() {
+    let mut screen = screen(5, 10, "\x1b[3;3H");
+
+    screen.process(b"\x1b[A");
+    assert_eq!(cursor(&screen), (1, 2));
+    screen.process(b"\x1b[2B");
+    assert_eq!(cursor(&screen), (3, 2));
+    screen.process(b"\x1b[4C");
+    assert_eq!(cursor(&screen), (3, 6));
+    screen.process(b"\x1b[D");
+    assert_eq!(cursor(&screen), (3, 5));
+
+    screen.process(b"\x1b[99A\x1b[99D");
+    assert_eq!(cursor(&screen), (0, 0));
+    screen.process(b"\x1b[99B\x1b[99C");
+    assert_eq!(cursor(&screen), (4, 9));
+}
+
+#[test]
+fn absolute_positions_are_one_based() {
+    let mut screen = screen(5, 10, "\x1b[2;4H");
+    assert_eq!(cursor(&screen), (1, 3));
+
+    screen.process(b"\x1b[3;5f");
+    assert_eq!(cursor(&screen), (2, 4));
+    screen.process(b"\x1b[H");
+    assert_eq!(cursor(&screen), (0, 0));
+    screen.process(b"\x1b[0;0H");
+    assert_eq!(cursor(&screen), (0, 0));
+    screen.process(b"\x1b[50;50H");
+    assert_eq!(cursor(&screen), (4, 9));
+
+    screen.process(b"\x1b[3G");
+    assert_eq!(cursor(&screen), (4, 2));
+    screen.process(b"\x1b[2d");
+    assert_eq!(cursor(&screen), (1, 2));
+}
+
+#[test]
+fn next_and_previous_line_return_to_first_column() {
+    let mut screen = screen(5, 10, "\x1b[3;6H");
+
+    screen.process(b"\x1b[E");
+    assert_eq!(cursor(&screen), (3, 0));
+    screen.process(b"\x1b[4G\x1b[2F");
+    assert_eq!(cursor(&screen), (1, 0));
+}
+
+#[test]
+fn erase_display() {
+    let below = screen(5, 10, &format!("{FILLED}\x1b[3;5H\x1b[J"));
+    assert_eq!(below.contents(), "0123456789\n0123456789\n0123");
+    assert_eq!(cursor(&below), (2, 4));
+
+    let above = screen(5, 10, &format!("{FILLED}\x1b[3;5H\x1b[1J"));
+    assert_eq!(above.contents(), "\n\n     56789\n0123456789\n0123456789");
+
+    let all = screen(5, 10, &format!("{FILLED}\x1b[3;5H\x1b[2J"));
+    assert_eq!(all.contents(), "");
+    assert_eq!(cursor(&all), (2, 4));
+}
+
+#[test]
+fn erase_line() {
+    let right = screen(1, 10, "0123456789\x1b[4G\x1b[K");
+    assert_eq!(right.contents(), "012");
+
+    let left = screen(1, 10, "0123456789\x1b[4G\x1b[1K");
+    assert_eq!(left.contents(), "    456789");
+
+    let all = screen(1, 10, "0123456789\x1b[4G\x1b[2K");
+    assert_eq!(all.contents(), "");
+    assert_eq!(cursor(&all), (0, 3));
+}
+
+#[test]
+fn erase_chars_leaves_the_cursor() {
+    let mut screen = screen(1, 10, "0123456789\x1b[3G\x1b[3X");
+    assert_eq!(screen.contents(), "01   56789");
+    assert_eq!(cursor(&screen), (0, 2));
+
+    screen.process(b"\x1b[99X");
+    assert_eq!(screen.contents(), "01");
+}
+
+#[test]
+fn erasing_keeps_the_background() {
+    let screen = screen(2, 10, "\x1b[44m\x1b[2J");
+    let cell = screen.cell(1, 5).unwrap();
+
+    assert!(cell.is_blank());
+    assert_eq!(cell.style.bg, Color::Indexed(4));
+}
+
+#[test]
+fn insert_and_delete_chars() {
+    let mut screen = screen(1, 10, "0123456789\x1b[3G\x1b[2@");
+    assert_eq!(screen.contents(), "01  234567");
+    assert_eq!(cursor(&screen), (0, 2));
+
+    screen.process(b"\x1b[4P");
+    assert_eq!(screen.contents(), "014567");
+
+    screen.process(b"\x1b[99P");
+    assert_eq!(screen.contents(), "01");
+}
+
+#[test]
+fn insert_and_delete_lines() {
+    let mut screen = screen(5, 10, &format!("{FILLED}\x1b[2;5H\x1b[2L"));
+    assert_eq!(screen.contents(), "0123456789\n\n\n0123456789\n0123456789");
+    assert_eq!(cursor(&screen), (1, 0));
+
+    screen.process(b"\x1b[3M");
+    assert_eq!(screen.contents(), "0123456789\n0123456789");
+}
+
+#[test]
+fn index_and_reverse_index_scroll_at_the_edges() {
+    let mut screen = screen(3, 10, "a\r\nb\r\nc\x1bD");
+    assert_eq!(screen.contents(), "b\nc");
+    assert_eq!(cursor(&screen), (2, 1));
+
+    screen.process(b"\x1b[H\x1bM");
+    assert_eq!(screen.contents(), "\nb\nc");
+    assert_eq!(cursor(&screen), (0, 0));
+
+    screen.process(b"\x1b[1;4H\x1bE");
+    assert_eq!(cursor(&screen), (1, 0));
+}
+
+#[test]
+fn wraps_after_the_last_column() {
+    let mut screen = screen(2, 5, "abcde");
+    assert_eq!(cursor(&screen), (0, 4));
+    assert!(screen.cursor().pending_wrap);
+
+    screen.process(b"f");
+    assert_eq!(screen.contents(), "abcde\nf");
+    assert!(screen.row(0).unwrap().is_wrapped());
+}
+
+#[test]
+fn scroll_region_keeps_rows_outside_it() {
+    let mut screen = screen(5, 10, &format!("{FILLED}\x1b[2;4r"));
+    assert_eq!(cursor(&screen), (0, 0));
+
+    screen.process(b"\x1b[4;1Hx\nnew");
+    assert_eq!(
+        screen.contents(),
+        "0123456789\n0123456789\nx123456789\n new\n0123456789"
+    );
+    assert!(screen.scrollback().is_empty());
+
+    screen.process(b"\x1b[2;1H\x1bM\x1b[2T");
+    assert_eq!(screen.contents(), "0123456789\n\n\n\n0123456789");
+}
+
+#[test]
+fn only_whole_screen_scrolls_reach_the_scrollback() {
+    let mut screen = screen(3, 10, "a\r\nb\r\nc\r\nd");
+    assert_eq!(screen.scrollback().len(), 1);
+
+    screen.process(b"\x1b[2S");
+    assert_eq!(screen.scrollback().len(), 3);
+    assert_eq!(screen.scrollback().iter().last().unwrap().text(), "c");
+
+    screen.process(b"\x1b[1;2r\x1b[S\x1b[r\x1b[H\x1b[M");
+    assert_eq!(screen.scrollback().len(), 3);
+}
+
+#[test]
+fn origin_mode_addresses_the_region() {
+    let mut screen = screen(5, 10, "\x1b[2;4r\x1b[?6h");
+    assert_eq!(cursor(&screen), (1, 0));
+
+    screen.process(b"\x1b[2;3H");
+    assert_eq!(cursor(&screen), (2, 2));
+    screen.process(b"\x1b[9;1H");
+    assert_eq!(cursor(&screen), (3, 0));
+
+    screen.process(b"\x1b[?6l");
+    assert_eq!(cursor(&screen), (0, 0));
+}
+
+#[test]
+fn left_right_margins() {
+    let mut screen = screen(3, 10, &format!("{FILLED}\x1b[3;7s"));
+    assert_eq!(screen.contents(), "0123456789\n0123456789\n0123456789");
+
+    // Ignored until DECLRMM is set
+    screen.process(b"\x1b[?69h\x1b[3;7s\x1b[1;3Habcdefg");
+    assert_eq!(screen.contents(), "01abcde789\n01fg456789\n0123456789");
+
+    screen.process(b"\x1b[3;3H\x1b[S");
+    assert_eq!(screen.contents(), "01fg456789\n0123456789\n01     789");
+}
+
+#[test]
+fn alternate_screen_restores_the_primary() {
+    let mut screen = screen(3, 10, "shell$ \x1b[1m");
+    assert_eq!(screen.buffer(), Buffer::Primary);
+
+    screen.process(b"\x1b[?1049h\x1b[0m\x1b[2;1Heditor");
+    assert_eq!(screen.buffer(), Buffer::Alternate);
+    assert_eq!(screen.contents(), "\neditor");
+
+    screen.process(b"\r\n\n\n\n");
+    assert!(screen.scrollback().is_empty());
+
+    screen.process(b"\x1b[?1049l");
+    assert!(!screen.is_alternate());
+    assert_eq!(screen.contents(), "shell$");
+    assert_eq!(cursor(&screen), (0, 7));
+    assert!(screen.cursor().style.attrs.contains(Attrs::BOLD));
+}
+
+#[test]
+fn alternate_screen_modes_47_and_1047() {
+    let mut screen = screen(2, 10, "primary\x1b[?47halt");
+    assert_eq!(screen.contents(), "       alt");
+
+    screen.process(b"\x1b[?47l");
+    assert_eq!(screen.contents(), "primary");
+
+    // 47 keeps what was drawn, 1047 clears it on the way out
+    screen.process(b"\x1b[?47h");
+    assert_eq!(screen.contents(), "       alt");
+    screen.process(b"\x1b[?1047l\x1b[?1047h");
+    assert_eq!(screen.contents(), "");
+}
+
+#[test]
+fn save_and_restore_cursor() {
+    let mut screen = screen(5, 10, "\x1b[2;3H\x1b[31m\x1b7\x1b[H\x1b[m\x1b8");
+    assert_eq!(cursor(&screen), (1, 2));
+    assert_eq!(screen.cursor().style.fg, Color::Indexed(1));
+
+    screen.process(b"\x1b[4;4H\x1b[s\x1b[H\x1b[u");
+    assert_eq!(cursor(&screen), (3, 3));
+}
+
+#[test]
+fn scrollback_keeps_the_newest_rows() {
+    let mut screen = Screen::new(2, 10);
+    screen.scrollback_mut().set_max_lines(Some(3));
+
+    for i in 0..10 {
+        screen.process(format!("{i}\r\n").as_bytes());
+    }
+
+    let history = screen.scrollback();
+    assert_eq!(history.len(), 3);
+    assert_eq!(history[0].text(), "6");
+    assert_eq!(history.get(2).unwrap().text(), "8");
+    assert!(history.get(3).is_none());
+    assert_eq!(
+        history
+            .iter()
+            .rev()
+            .map(|row| row.text())
+            .collect::<Vec<_>>(),
+        ["8", "7", "6"]
+    );
+}
+
+#[test]
+fn scrollback_byte_limit() {
+    let mut screen = Screen::new(1, 80);
+    screen.process(b"a\r\nb\r\nc\r\n");
+
+    let row = screen.scrollback().bytes() / screen.scrollback().len();
+    screen.scrollback_mut().set_max_bytes(Some(row * 2));
+    assert_eq!(screen.scrollback().len(), 2);
+
+    screen.process(b"d\r\n");
+    assert_eq!(screen.scrollback().len(), 2);
+    assert_eq!(screen.scrollback()[1].text(), "d");
+    assert!(screen.scrollback().bytes() <= row * 2);
+}
+
+#[test]
+fn clearing_the_scrollback() {
+    let mut screen = screen(1, 10, "a\r\nb\r\nc");
+    assert_eq!(screen.scrollback().len(), 2);
+
+    screen.process(b"\x1b[3J");
+    assert!(screen.scrollback().is_empty());
+    assert_eq!(screen.contents(), "c");
+
+    screen.process(b"\r\nd");
+    screen.scrollback_mut().clear();
+    assert!(screen.scrollback().is_empty());
+}
+
+#[test]
+fn shrinking_and_growing_restores_wrapped_lines() {
+    let mut screen = screen(3, 10, "$ echo 0123456789\r\n0123456789\r\n$ ");
+    assert_eq!(screen.contents(), "3456789\n0123456789\n$");
+    assert_eq!(screen.scrollback()[0].text(), "$ echo 012");
+
+    screen.resize(3, 20);
+    assert_eq!(screen.contents(), "$ echo 0123456789\n0123456789\n$");
+    assert_eq!(cursor(&screen), (2, 2));
+
+    screen.resize(3, 5);
+    assert_eq!(screen.contents(), "01234\n56789\n$");
+    assert_eq!(cursor(&screen), (2, 2));
+    assert_eq!(
+        screen
+            .scrollback()
+            .iter()
+            .map(|row| row.text())
+            .collect::<Vec<_>>(),
+        ["$ ech", "o 012", "34567", "89"]
+    );
+
+    screen.resize(3, 20);
+    assert_eq!(screen.contents(), "$ echo 0123456789\n0123456789\n$");
+    assert_eq!(cursor(&screen), (2, 2));
+}
+
+#[test]
+fn resizing_keeps_the_cursor_within_its_line() {
+    let mut screen = screen(4, 10, "abcdefghij\x1b[2;1Hxyz\x1b[1;8H");
+
+    screen.resize(4, 4);
+    assert_eq!(screen.contents(), "abcd\nefgh\nij\nxyz");
+    assert_eq!(cursor(&screen), (1, 3));
+
+    // Growing the height pulls rows back out of the scrollback
+    screen.resize(3, 4);
+    assert_eq!(screen.contents(), "efgh\nij\nxyz");
+    screen.resize(4, 4);
+    assert_eq!(screen.contents(), "abcd\nefgh\nij\nxyz");
+}
+
+#[test]
+fn alternate_screen_is_not_reflowed() {
+    let mut screen = screen(2, 10, "0123456789ab\x1b[?1049h\x1b[H0123456789ab");
+
+    screen.resize(2, 5);
+    assert_eq!(screen.contents(), "01234\nab");
+
+    screen.process(b"\x1b[?1049l");
+    assert_eq!(screen.contents(), "56789\nab");
+    assert_eq!(screen.scrollback().iter().last().unwrap().text(), "01234");
+    assert_eq!(cursor(&screen), (1, 2));
+}
+
+#[test]
+fn wide_characters_take_two_columns() {
+    let mut screen = screen(2, 5, "a漢字");
+    assert_eq!(screen.contents(), "a漢字");
+    assert_eq!(cursor(&screen), (0, 4));
+    assert!(screen.cell(0, 1).unwrap().is_wide());
+    assert!(screen.cell(0, 2).unwrap().is_spacer());
+
+    // No room for another: it wraps whole
+    screen.process("\x1b[5G字".as_bytes());
+    assert_eq!(screen.contents(), "a漢\n字");
+    assert!(screen.row(0).unwrap().is_wrapped());
+
+    // Overwriting half of one erases the other
+    screen.process(b"\x1b[1;3Hx");
+    assert_eq!(screen.contents(), "a x\n字");
+}
+
+#[test]
+fn combining_marks_join_the_previous_cell() {
+    let screen = screen(1, 10, "e\u{301}x\u{1F44D}\u{1F3FD}!");
+    assert_eq!(screen.cell(0, 0).unwrap().grapheme(), "e\u{301}");
+    assert_eq!(screen.cell(0, 2).unwrap().grapheme(), "\u{1F44D}\u{1F3FD}");
+    assert_eq!(screen.cell(0, 4).unwrap().grapheme(), "!");
+    assert_eq!(cursor(&screen), (0, 5));
+}
+
+#[test]
+fn emoji_sequences_stay_together() {
+    let family = "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}";
+    let screen = screen(1, 10, &format!("{family}\u{1F1FA}\u{1F1F8}x"));
+
+    assert_eq!(screen.cell(0, 0).unwrap().grapheme(), family);
+    assert_eq!(screen.cell(0, 2).unwrap().grapheme(), "\u{1F1FA}\u{1F1F8}");
+    assert!(screen.cell(0, 2).unwrap().is_wide());
+    assert_eq!(screen.cell(0, 4).unwrap().grapheme(), "x");
+}
+
+#[test]
+fn unicode_version_selects_widths() {
+    let heart = "\u{2764}\u{FE0F}x";
+    let watch = "\u{231A}x";
+
+    let mut screen = Screen::new(1, 10);
+    screen.process(format!("{heart}{watch}").as_bytes());
+    assert_eq!(screen.cell(0, 1).unwrap().grapheme(), "x");
+    assert_eq!(screen.cell(0, 4).unwrap().grapheme(), "x");
+
+    let mut screen = Screen::new(1, 10);
+    screen.set_unicode_version(UnicodeVersion::Latest);
+    screen.process(format!("{heart}{watch}").as_bytes());
+    assert!(screen.cell(0, 0).unwrap().is_wide());
+    assert_eq!(screen.cell(0, 2).unwrap().grapheme(), "x");
+
+    let mut screen = Screen::new(1, 10);
+    screen.set_unicode_version(UnicodeVersion::V8);
+    screen.process(format!("{watch}漢").as_bytes());
+    assert_eq!(screen.cell(0, 1).unwrap().grapheme(), "x");
+    assert!(screen.cell(0, 2).unwrap().is_wide());
+}
+
+#[test]
+fn reflow_keeps_wide_characters_whole() {
+    let mut screen = screen(2, 6, "ab漢字");
+    screen.resize(2, 5);
+    assert_eq!(screen.contents(), "ab漢\n字");
+
+    screen.resize(2, 6);
+    assert_eq!(screen.contents(), "ab漢字");
+}
+
+#[test]
+fn reflow_rejoins_wide_characters_that_wrapped_early() {
+    let mut screen = screen(2, 5, "abcd漢");
+    assert_eq!(screen.contents(), "abcd\n漢");
+
+    screen.resize(2, 6);
+    assert_eq!(screen.contents(), "abcd漢");
+}
+
+#[test]
+fn default_tab_stops() {
+    let mut screen = screen(1, 20, "a\tb\tc\t");
+    assert_eq!(screen.contents(), "a       b       c");
+    assert_eq!(cursor(&screen), (0, 19));
+    assert_eq!(screen.tab_stops().collect::<Vec<_>>(), [8, 16]);
+
+    screen.process(b"\x1b[2Z");
+    assert_eq!(cursor(&screen), (0, 8));
+    screen.process(b"\x1b[9Z");
+    assert_eq!(cursor(&screen), (0, 0));
+    screen.process(b"\x1b[2I");
+    assert_eq!(cursor(&screen), (0, 16));
+}
+
+#[test]
+fn setting_and_clearing_tab_stops() {
+    let mut screen = screen(1, 20, "\x1b[3G\x1bH\x1b[9G\x1b[g\r\t");
+    assert_eq!(cursor(&screen), (0, 2));
+    assert_eq!(screen.tab_stops().collect::<Vec<_>>(), [2, 16]);
+
+    screen.process(b"\t");
+    assert_eq!(cursor(&screen), (0, 16));
+
+    screen.process(b"\x1b[3g\r\t");
+    assert_eq!(cursor(&screen), (0, 19));
+    assert_eq!(screen.tab_stops().count(), 0);
+}
+
+#[test]
+fn tab_stops_on_resize() {
+    let mut screen = screen(1, 10, "\x1b[3G\x1bH");
+    assert_eq!(screen.tab_stops().collect::<Vec<_>>(), [2, 8]);
+
+    screen.resize(1, 20);
+    assert_eq!(screen.tab_stops().collect::<Vec<_>>(), [2, 8, 16]);
+
+    screen.resize(1, 5);
+    assert_eq!(screen.tab_stops().collect::<Vec<_>>(), [2]);
+}
+
+#[test]
+fn line_drawing_charset() {
+    let mut screen = screen(3, 10, "\x1b(0lqqk\r\nx  x\r\nmqqj\x1b(B");
+    assert_eq!(screen.contents(), "┌──┐\n│  │\n└──┘");
+
+    screen.process(b"\x1b[Hlqk");
+    assert_eq!(screen.row(0).unwrap().text(), "lqk┐");
+}
+
+#[test]
+fn locking_and_single_shifts() {
+    let mut screen = screen(1, 20, "\x1b)0\x1b*A\x1b+<a\x0eq\x0fq\x1bN#\x1bO(#");
+    assert_eq!(screen.contents(), "a─q£¤#");
+
+    screen.process(b"\x1bn#\x1bo(W\x0fW");
+    assert_eq!(screen.contents(), "a─q£¤#£¤ŒW");
+}
+
+#[test]
+fn latin1_text_is_left_alone() {
+    // Decoded 0xa0-0xff never go through GR, nor through a single shift, which they use up
+    let mut screen = screen(2, 20, "\x1b)0\x1b~café £5");
+    assert_eq!(screen.contents(), "café £5");
+
+    screen.process("\r\n\x1b*0\x1bNécafé".as_bytes());
+    assert_eq!(screen.contents(), "café £5\nécafé");
+}
+
+#[test]
+fn saved_cursor_keeps_the_charsets() {
+    let screen = screen(1, 10, "\x1b(0\x1b7\x1b(Bq\x1b8\x1b[2Gq");
+    assert_eq!(screen.contents(), "q─");
+}
+
+#[test]
+fn overwriting_the_spacer_of_an_early_wrap() {
+    let screen = screen(3, 5, "abcd漢\x1b[1;5Hx");
+    assert_eq!(screen.contents(), "abcdx\n漢");
+}
+
+#[test]
+fn erasing_part_of_an_early_wrapped_row_drops_the_spacer() {
+    let screen = screen(3, 5, "abcd漢\x1b[1;1H\x1b[1K");
+
+    assert_eq!(screen.contents(), " bcd\n漢");
+    assert!(!screen.row(0).unwrap().is_wrapped());
+    assert!(!screen.cell(0, 4).unwrap().is_spacer());
+}
+
+#[test]
+fn early_wrap_inside_margins_leaves_no_spacer() {
+    let screen = screen(3, 5, "\x1b[?69h\x1b[1;4sabc漢");
+
+    assert_eq!(screen.contents(), "abc\n漢");
+    assert!(!screen.row(0).unwrap().is_wrapped());
+    assert!(!screen.cell(0, 3).unwrap().is_spacer());
+}
+
+#[test]
+fn private_modes_are_tracked() {
+    let mut screen = Screen::new(2, 10);
+    assert!(screen.mode(Mode::Autowrap));
+    assert!(screen.mode(Mode::CursorVisible));
+    assert!(!screen.mode(Mode::BracketedPaste));
+
+    screen.process(b"\x1b[?1;5;1004;2004;2026h\x1b[?25l");
+    for mode in [
+        Mode::CursorKeys,
+        Mode::ReverseVideo,
+        Mode::FocusEvents,
+        Mode::BracketedPaste,
+        Mode::SynchronizedOutput,
+    ] {
+        assert!(screen.mode(mode), "{mode:?}");
+    }
+    assert!(!screen.mode(Mode::CursorVisible));
+
+    screen.set_mode(Mode::BracketedPaste, false);
+    assert!(!screen.mode(Mode::BracketedPaste));
+    assert_eq!(Mode::from_number(2004), Some(Mode::BracketedPaste));
+    assert_eq!(Mode::SynchronizedOutput.number(), 2026);
+}
+
+#[test]
+fn mode_requests_are_answered_from_the_table() {
+    let mut screen = screen(
+        2,
+        10,
+        "\x1b[?2004h\x1b[?2004$p\x1b[?7$p\x1b[?1$p\x1b[?9999$p\x1b[4$p",
+    );
+    assert_eq!(
+        screen.take_responses(),
+        b"\x1b[?2004;1$y\x1b[?7;1$y\x1b[?1;2$y\x1b[?9999;0$y\x1b[4;0$y"
+    );
+    assert!(screen.take_responses().is_empty());
+
+    screen.set_mode(Mode::Autowrap, false);
+    screen.process(b"\x1b[?1049h\x1b[?7$p\x1b[?1049$p");
+    assert_eq!(screen.take_responses(), b"\x1b[?7;2$y\x1b[?1049;1$y");
+}
+
+#[test]
+fn without_autowrap_the_last_column_is_overwritten() {
+    let mut screen = screen(3, 5, "\x1b[?7labcdefg");
+    assert_eq!(screen.contents(), "abcdg");
+    assert_eq!(cursor(&screen), (0, 4));
+
+    screen.process(b"\x1b[?7h\r\nabcdefg");
+    assert_eq!(screen.contents(), "abcdg\nabcde\nfg");
+}
+
+#[test]
+fn reverse_wraparound() {
+    let mut screen = screen(2, 5, "\x1b[2;1H\x08");
+    assert_eq!(cursor(&screen), (1, 0));
+
+    screen.process(b"\x1b[?45h\x08");
+    assert_eq!(cursor(&screen), (0, 4));
+}
+
+#[test]
+fn origin_mode_is_saved_with_the_cursor() {
+    let mut screen = screen(5, 10, "\x1b[2;4r\x1b[?6h\x1b7\x1b[?6l");
+    assert!(!screen.mode(Mode::Origin));
+
+    screen.process(b"\x1b8");
+    assert!(screen.mode(Mode::Origin));
+}
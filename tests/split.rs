@@ -0,0 +1,75 @@
+use std::{
+    io::{Read, Write},
+    process::Command,
+    thread,
+};
+use termu::{Signal, Winsize};
+
+mod common;
+use common::sh;
+
+#[test]
+fn halves_work_from_other_threads() {
+    let mut terminal = sh("read line; echo \"got $line\"; stty size; sleep 10");
+    let (mut reader, mut writer, control) = terminal.split().unwrap();
+
+    let output = thread::spawn(move || {
+        let mut output = Vec::new();
+        let mut buf = [0; 64];
+
+        while !output.ends_with(b"5 20\r\n") {
+            match reader.read(&mut buf).unwrap() {
+                0 => break,
+                n => output.extend_from_slice(&buf[..n]),
+            }
+        }
+
+        (reader, String::from_utf8(output).unwrap())
+    });
+
+    let control = thread::spawn(move || {
+        let size = Winsize {
+            ws_row: 5,
+            ws_col: 20,
+            ws_xpixel: 0,
+            ws_ypixel: 0,
+        };
+        control.resize(size).unwrap();
+        control
+    })
+    .join()
+    .unwrap();
+
+    thread::spawn(move || writer.write_all(b"hello\n").unwrap())
+        .join()
+        .unwrap();
+
+    let (_reader, output) = output.join().unwrap();
+    assert_eq!(output, "hello\r\ngot hello\r\n5 20\r\n");
+
+    let signaller = control.clone();
+    thread::spawn(move || signaller.signal(Signal::SIGTERM).unwrap())
+        .join()
+        .unwrap();
+
+    let reason = terminal.wait().unwrap();
+    assert_eq!(reason.signal(), Some(Signal::SIGTERM));
+}
+
+#[test]
+fn control_follows_the_child() {
+    let mut terminal = sh("exit 0");
+    let (_reader, _writer, control) = terminal.split().unwrap();
+    assert_eq!(control.pid(), terminal.pid());
+
+    terminal.wait().unwrap();
+    // The pid may already belong to someone else
+    assert_eq!(control.pid(), None);
+    assert!(control.signal(Signal::SIGTERM).is_err());
+
+    terminal.attach(&mut Command::new("true")).unwrap();
+    assert_eq!(control.pid(), terminal.pid());
+    assert!(control.pid().is_some());
+
+    terminal.wait().unwrap();
+}
@@ -0,0 +1,108 @@
+use std::io::Write;
+use termu::{
+    termios::{InputFlags, LocalFlags, OutputFlags, SpecialCharacterIndices},
+    Terminal,
+};
+
+mod common;
+use common::{read_all, sh};
+
+fn local(terminal: &Terminal) -> LocalFlags {
+    terminal.termios().unwrap().local_flags
+}
+
+fn control_char(terminal: &Terminal, index: SpecialCharacterIndices) -> u8 {
+    terminal.termios().unwrap().control_chars[index as usize]
+}
+
+#[test]
+fn raw_and_cooked() {
+    let terminal = sh("sleep 10");
+
+    terminal.set_raw().unwrap();
+    let termios = terminal.termios().unwrap();
+    assert!(!termios
+        .local_flags
+        .intersects(LocalFlags::ICANON | LocalFlags::ECHO | LocalFlags::ISIG));
+    assert!(!termios.output_flags.contains(OutputFlags::OPOST));
+
+    terminal.set_cooked().unwrap();
+    let termios = terminal.termios().unwrap();
+    assert!(termios
+        .local_flags
+        .contains(LocalFlags::ICANON | LocalFlags::ECHO | LocalFlags::ISIG));
+    assert!(termios
+        .output_flags
+        .contains(OutputFlags::OPOST | OutputFlags::ONLCR));
+    assert_eq!(
+        control_char(&terminal, SpecialCharacterIndices::VINTR),
+        0x03
+    );
+    assert_eq!(control_char(&terminal, SpecialCharacterIndices::VEOF), 0x04);
+}
+
+#[test]
+fn flags_round_trip() {
+    let terminal = sh("sleep 10");
+
+    terminal.set_echo(false).unwrap();
+    assert!(!local(&terminal).contains(LocalFlags::ECHO));
+    terminal.set_echo(true).unwrap();
+    assert!(local(&terminal).contains(LocalFlags::ECHO));
+
+    terminal.set_canonical(false).unwrap();
+    assert!(!local(&terminal).contains(LocalFlags::ICANON));
+    terminal.set_canonical(true).unwrap();
+    assert!(local(&terminal).contains(LocalFlags::ICANON));
+
+    terminal.set_output_processing(false).unwrap();
+    let output = terminal.termios().unwrap().output_flags;
+    assert!(!output.contains(OutputFlags::OPOST));
+    terminal.set_output_processing(true).unwrap();
+    let output = terminal.termios().unwrap().output_flags;
+    assert!(output.contains(OutputFlags::OPOST));
+}
+
+#[test]
+#[cfg(any(target_os = "linux", target_os = "android", target_vendor = "apple"))]
+fn utf8_round_trips() {
+    let terminal = sh("sleep 10");
+
+    terminal.set_utf8(false).unwrap();
+    let input = terminal.termios().unwrap().input_flags;
+    assert!(!input.contains(InputFlags::IUTF8));
+    terminal.set_utf8(true).unwrap();
+    let input = terminal.termios().unwrap().input_flags;
+    assert!(input.contains(InputFlags::IUTF8));
+}
+
+#[test]
+fn control_chars_round_trip() {
+    let terminal = sh("sleep 10");
+
+    terminal.set_interrupt_char(0x01).unwrap();
+    assert_eq!(
+        control_char(&terminal, SpecialCharacterIndices::VINTR),
+        0x01
+    );
+
+    terminal.set_eof_char(0x02).unwrap();
+    assert_eq!(control_char(&terminal, SpecialCharacterIndices::VEOF), 0x02);
+}
+
+#[test]
+fn child_sees_changes() {
+    let mut terminal = sh("read line; stty -a");
+
+    terminal.set_echo(false).unwrap();
+    terminal.set_interrupt_char(0x01).unwrap();
+    terminal.stdin.as_mut().unwrap().write_all(b"\n").unwrap();
+
+    // Nothing is echoed back, so the output starts with `stty`'s
+    let output = read_all(&mut terminal);
+    assert!(output.starts_with("speed"), "{output:?}");
+
+    let settings = output.split_whitespace().collect::<Vec<_>>();
+    assert!(settings.contains(&"-echo"));
+    assert!(settings.windows(3).any(|w| w == ["intr", "=", "^A;"]));
+}